@@ -18,7 +18,7 @@ create table tasks (
 	notes            TEXT
 );
 
-insert into tasks values (0, 0, "未指定", 0, 0, NULL);
+insert into tasks values (0, 0, '未指定', 0, 0, NULL);
 
 
 create table inventory (
@@ -27,5 +27,5 @@ create table inventory (
 	color			 INT NOT NULL
 );
 
-insert into inventory values (0, "未指定", 0);
+insert into inventory values (0, '未指定', 0);
 
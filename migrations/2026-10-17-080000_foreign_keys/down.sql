@@ -0,0 +1,36 @@
+-- rebuild tables without foreign keys
+
+alter table tomatos rename to tomatos_old;
+alter table tasks rename to tasks_old;
+alter table inventory rename to inventory_old;
+
+create table inventory (
+	id               INTEGER PRIMARY KEY NOT NULL,
+	name             TEXT NOT NULL,
+	color			 INT NOT NULL
+);
+
+create table tasks (
+	id               INTEGER PRIMARY KEY NOT NULL,
+	inventory_id	 INT NOT NULL,
+	name             TEXT NOT NULL,
+	spent_minutes    BIGINT NOT NULL,
+	create_at        BIGINT NOT NULL,
+	notes            TEXT
+);
+
+create table tomatos (
+	id               INTEGER PRIMARY KEY NOT NULL, -- alias for rowid
+	inventory_id     INT NOT NULL,
+	task_id          INT NOT NULL,
+	start_time       BIGINT NOT NULL,
+	end_time         BIGINT NOT NULL
+);
+
+insert into inventory select * from inventory_old;
+insert into tasks select * from tasks_old;
+insert into tomatos select * from tomatos_old;
+
+drop table tomatos_old;
+drop table tasks_old;
+drop table inventory_old;
@@ -0,0 +1,46 @@
+-- SQLite can't add constraints to existing tables, rebuild them
+
+alter table tomatos rename to tomatos_old;
+alter table tasks rename to tasks_old;
+alter table inventory rename to inventory_old;
+
+create table inventory (
+	id               INTEGER PRIMARY KEY NOT NULL,
+	name             TEXT NOT NULL,
+	color			 INT NOT NULL
+);
+
+create table tasks (
+	id               INTEGER PRIMARY KEY NOT NULL,
+	inventory_id	 INT NOT NULL REFERENCES inventory(id) ON DELETE CASCADE,
+	name             TEXT NOT NULL,
+	spent_minutes    BIGINT NOT NULL,
+	create_at        BIGINT NOT NULL,
+	notes            TEXT
+);
+
+create table tomatos (
+	id               INTEGER PRIMARY KEY NOT NULL, -- alias for rowid
+	inventory_id     INT NOT NULL REFERENCES inventory(id) ON DELETE CASCADE,
+	task_id          INT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+	start_time       BIGINT NOT NULL,
+	end_time         BIGINT NOT NULL
+);
+
+insert into inventory select id, name, color from inventory_old;
+
+-- rows orphaned by the former hand-made deletions are dropped
+insert into tasks select * from tasks_old
+	where inventory_id in (select id from inventory);
+
+insert into tomatos select * from tomatos_old
+	where inventory_id in (select id from inventory)
+	and task_id in (select id from tasks);
+
+drop table tomatos_old;
+drop table tasks_old;
+drop table inventory_old;
+
+create index tasks_inventory_id on tasks (inventory_id);
+create index tomatos_inventory_id on tomatos (inventory_id);
+create index tomatos_task_id on tomatos (task_id);
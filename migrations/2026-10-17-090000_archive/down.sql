@@ -0,0 +1,2 @@
+alter table inventory drop column archived_at;
+alter table tasks drop column archived_at;
@@ -0,0 +1,4 @@
+-- archived rows are hidden from lists, but their tomatos are kept for statistics
+
+alter table inventory add column archived_at BIGINT;
+alter table tasks add column archived_at BIGINT;
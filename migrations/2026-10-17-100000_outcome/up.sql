@@ -0,0 +1,3 @@
+-- 0: completed, 1: interrupted, 2: skipped. Rows before this are all completed
+
+alter table tomatos add column outcome INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+alter table tomatos drop column paused_seconds;
@@ -0,0 +1,3 @@
+-- focused time of a tomato is end_time - start_time - paused_seconds
+
+alter table tomatos add column paused_seconds BIGINT NOT NULL DEFAULT 0;
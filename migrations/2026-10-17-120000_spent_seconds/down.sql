@@ -0,0 +1 @@
+alter table tasks rename column spent_seconds to spent_minutes;
@@ -0,0 +1,8 @@
+-- spent_minutes always held seconds, name it so and recompute it from the tomatos
+
+alter table tasks rename column spent_minutes to spent_seconds;
+
+update tasks set spent_seconds = coalesce(
+	(select sum(end_time - start_time - paused_seconds) from tomatos where tomatos.task_id = tasks.id),
+	0
+);
@@ -0,0 +1,14 @@
+-- the countdown left running when the app quit, one row at most
+
+create table session (
+	id                   INTEGER PRIMARY KEY NOT NULL CHECK (id = 0),
+	phase_idx            INT NOT NULL,
+	long_break_interval  INT NOT NULL,
+	duration             BIGINT NOT NULL,
+	left_seconds         BIGINT NOT NULL,
+	paused               BOOLEAN NOT NULL,
+	task_id              INT,
+	started_at           BIGINT,
+	paused_seconds       BIGINT NOT NULL,
+	saved_at             BIGINT NOT NULL
+);
@@ -0,0 +1,9 @@
+-- completed tomatoes to reach per day and per week, 0 for no goal
+
+create table goals (
+	id               INTEGER PRIMARY KEY NOT NULL,
+	-- NULL for all inventories together
+	inventory_id     INT REFERENCES inventory(id) ON DELETE CASCADE,
+	daily            INT NOT NULL,
+	weekly           INT NOT NULL
+);
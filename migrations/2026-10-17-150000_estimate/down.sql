@@ -0,0 +1,2 @@
+alter table tasks drop column spent_tomatos;
+alter table tasks drop column estimate;
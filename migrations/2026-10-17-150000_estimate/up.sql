@@ -0,0 +1,9 @@
+-- tomatoes a task is expected to take, NULL if not estimated
+
+alter table tasks add column estimate INT;
+
+-- completed tomatoes of a task, kept along with spent_seconds
+alter table tasks add column spent_tomatos INT NOT NULL DEFAULT 0;
+
+update tasks set spent_tomatos =
+	(select count(*) from tomatos where tomatos.task_id = tasks.id and outcome = 0);
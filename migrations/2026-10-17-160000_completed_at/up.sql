@@ -0,0 +1,3 @@
+-- unix time a task was marked done, NULL while open
+
+alter table tasks add column completed_at BIGINT;
@@ -1,3 +1,229 @@
-struct ColorTheme {
-    
-}
\ No newline at end of file
+use std::error::Error;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate};
+use structopt::StructOpt;
+
+use crate::config::Config;
+use crate::db::{DbUtils, Inventory, Outcome, Task, Tomato};
+use crate::models::{
+    format_duration, local_timestamp, parse_inv_input, week_of, StatRange, Statistics,
+};
+use crate::process::handle_tomota_close;
+
+#[derive(StructOpt)]
+#[structopt(name = "tomato", about = "A pomodoro timer in terminal")]
+pub struct Opt {
+    #[structopt(subcommand)]
+    pub cmd: Option<Command>,
+}
+
+#[derive(StructOpt)]
+pub enum Command {
+    /// Run a focus countdown for <inventory>/<task>
+    Start { target: String },
+    /// Show tomatoes of today and this week
+    Status,
+    /// List inventories or tasks
+    List(ListTarget),
+    /// Create an inventory or a task
+    Add(AddTarget),
+    /// Show focus time per day and per inventory
+    Stats {
+        /// the first date included, as YYYY-MM-DD. 6 days ago by default
+        #[structopt(long, parse(try_from_str = parse_date))]
+        since: Option<NaiveDate>,
+    },
+}
+
+#[derive(StructOpt)]
+pub enum ListTarget {
+    Inventories,
+    Tasks {
+        /// only tasks in this inventory
+        inventory: Option<String>,
+    },
+}
+
+#[derive(StructOpt)]
+pub enum AddTarget {
+    /// <name>[@color]
+    Inventory { name: String },
+    /// <inventory>/<task>
+    Task {
+        target: String,
+        #[structopt(long)]
+        notes: Option<String>,
+    },
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate, chrono::ParseError> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+}
+
+// "inventory/task" -> ("inventory", "task")
+fn split_target(target: &str) -> Result<(&str, &str), Box<dyn Error>> {
+    let parts: Vec<_> = target.splitn(2, '/').collect();
+    if parts.len() != 2 || parts[0].is_empty() || parts[1].is_empty() {
+        return Err(format!("expect <inventory>/<task>, got {:?}", target).into());
+    }
+    Ok((parts[0], parts[1]))
+}
+
+fn find_inventory(name: &str) -> Result<Inventory, Box<dyn Error>> {
+    let inv = DbUtils::all_inventory()?
+        .into_iter()
+        .find(|i| i.name == name)
+        .ok_or_else(|| format!("no inventory named {:?}", name))?;
+    Ok(inv)
+}
+
+fn find_task(target: &str) -> Result<(Inventory, Task), Box<dyn Error>> {
+    let (inv_name, task_name) = split_target(target)?;
+    let inv = find_inventory(inv_name)?;
+    let task = DbUtils::all_task_groupby(std::slice::from_ref(&inv))?
+        .pop()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|t| t.name == task_name)
+        .ok_or_else(|| format!("no task named {:?} in {:?}", task_name, inv_name))?;
+    Ok((inv, task))
+}
+
+pub async fn run(cmd: Command, config: Config) -> Result<(), Box<dyn Error>> {
+    match cmd {
+        Command::Start { target } => start(&target, &config).await,
+        Command::Status => status(),
+        Command::List(what) => list(what),
+        Command::Add(what) => add(what),
+        Command::Stats { since } => stats(since),
+    }
+}
+
+async fn start(target: &str, config: &Config) -> Result<(), Box<dyn Error>> {
+    let (inv, task) = find_task(target)?;
+    let start_time = chrono::Utc::now().timestamp();
+    let total = config.timer.task_duration;
+
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+    for passed in 0..=total {
+        interval.tick().await;
+        let left = total - passed;
+        print!(
+            "\r{} · {}  {:02}:{:02}",
+            inv.name,
+            task.name,
+            left / 60,
+            left % 60
+        );
+        let _ = io::stdout().flush();
+    }
+    println!("\x07\n🍅 done");
+
+    handle_tomota_close(Box::new(Tomato {
+        inventory_id: inv.id,
+        task_id: task.id,
+        start_time,
+        end_time: chrono::Utc::now().timestamp(),
+        outcome: Outcome::Completed as i32,
+        paused_seconds: 0,
+    }))
+    .await?;
+    Ok(())
+}
+
+// tomatos completed and time focused since the local day
+fn focus_since(day: NaiveDate) -> Result<(i64, i64), Box<dyn Error>> {
+    let days = DbUtils::focus_per_day(local_timestamp(day))?;
+    Ok(days
+        .iter()
+        .fold((0, 0), |(c, s), d| (c + d.count, s + d.seconds)))
+}
+
+fn status() -> Result<(), Box<dyn Error>> {
+    let stat = Statistics::load(StatRange::Week)?;
+    let today = Local::today().naive_local();
+    let (count, seconds) = focus_since(today)?;
+    println!("today: {} 🍅, {}", count, format_duration(seconds));
+    let (count, seconds) = focus_since(week_of(today))?;
+    println!("week:  {} 🍅, {}", count, format_duration(seconds));
+    println!(
+        "streak: {} days, longest {} days",
+        stat.current_streak, stat.longest_streak
+    );
+    Ok(())
+}
+
+fn list(what: ListTarget) -> Result<(), Box<dyn Error>> {
+    match what {
+        ListTarget::Inventories => {
+            for inv in DbUtils::all_inventory()? {
+                println!("{}", inv.name);
+            }
+        }
+        ListTarget::Tasks { inventory } => {
+            let invs = match inventory {
+                Some(name) => vec![find_inventory(&name)?],
+                None => DbUtils::all_inventory()?,
+            };
+            let tasks = DbUtils::all_task_groupby(&invs)?;
+            for (inv, tasks) in invs.iter().zip(tasks.iter()) {
+                for task in tasks {
+                    println!(
+                        "{}/{}\t{}",
+                        inv.name,
+                        task.name,
+                        format_duration(task.spent_seconds)
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn add(what: AddTarget) -> Result<(), Box<dyn Error>> {
+    match what {
+        AddTarget::Inventory { name } => {
+            let (name, color) = parse_inv_input(&name);
+            if find_inventory(name).is_ok() {
+                return Err(format!("inventory {:?} exists", name).into());
+            }
+            DbUtils::create_new_inventory(name, color)?;
+        }
+        AddTarget::Task { target, notes } => {
+            let (inv_name, task_name) = split_target(&target)?;
+            let inv = find_inventory(inv_name)?;
+            if find_task(&target).is_ok() {
+                return Err(format!("task {:?} exists", target).into());
+            }
+            DbUtils::create_new_task(inv.id, task_name, notes.as_deref())?;
+        }
+    }
+    Ok(())
+}
+
+fn stats(since: Option<NaiveDate>) -> Result<(), Box<dyn Error>> {
+    let since = since.unwrap_or_else(|| Local::today().naive_local() - chrono::Duration::days(6));
+    let ts = local_timestamp(since);
+
+    for day in DbUtils::focus_per_day(ts)? {
+        println!(
+            "{}\t{} 🍅\t{}",
+            day.day,
+            day.count,
+            format_duration(day.seconds)
+        );
+    }
+    println!();
+    for inv in DbUtils::focus_per_inventory(ts)? {
+        println!(
+            "{}\t{} 🍅\t{}",
+            inv.name,
+            inv.count,
+            format_duration(inv.seconds)
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,246 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::hooks::HooksConfig;
+use crate::models::TomatoConfig;
+use crate::notifier::NotifyConfig;
+
+const CONFIG_DIR: &str = "tomato";
+const CONFIG_FILE: &str = "config.toml";
+const CONFIG_HEADER: &str =
+    "# tomato config, durations are in minutes, auto_start_delay is in seconds\n\n";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Serialize(toml::ser::Error),
+    Invalid(&'static str, String),
+    NoConfigDir,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "can't access {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "malformed {}: {}", path.display(), e),
+            ConfigError::Serialize(e) => write!(f, "can't serialize config: {}", e),
+            ConfigError::Invalid(field, reason) => write!(f, "invalid `{}`: {}", field, reason),
+            ConfigError::NoConfigDir => write!(f, "can't determine the config directory"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub timer: TomatoConfig,
+    pub notify: NotifyConfig,
+    pub hooks: HooksConfig,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/tomato/config.toml` on Linux
+    pub fn path() -> Result<PathBuf, ConfigError> {
+        dirs::config_dir()
+            .map(|dir| dir.join(CONFIG_DIR).join(CONFIG_FILE))
+            .ok_or(ConfigError::NoConfigDir)
+    }
+
+    /// Load config from file. A default one is written down if the file is absent,
+    /// so that users have something to edit.
+    pub fn load() -> Result<Config, ConfigError> {
+        let path = Config::path()?;
+        let config = match fs::read_to_string(&path) {
+            Ok(content) => {
+                toml::from_str::<Config>(&content).map_err(|e| ConfigError::Parse(path, e))?
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let config = Config::default();
+                // not being able to persist defaults should not stop the app
+                let _ = config.save();
+                config
+            }
+            Err(e) => return Err(ConfigError::Io(path, e)),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let path = Config::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| ConfigError::Io(dir.to_owned(), e))?;
+        }
+        let content = toml::to_string(self).map_err(ConfigError::Serialize)?;
+        fs::write(&path, CONFIG_HEADER.to_owned() + &content).map_err(|e| ConfigError::Io(path, e))
+    }
+
+    /// Write the timer settings down. Only the `[timer]` keys are touched, comments and
+    /// formatting elsewhere in the file stay as they are.
+    pub fn save_timer(&self) -> Result<(), ConfigError> {
+        let path = Config::path()?;
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return self.save(),
+            Err(e) => return Err(ConfigError::Io(path, e)),
+        };
+        let timer = toml::to_string(&self.timer).map_err(ConfigError::Serialize)?;
+        let content = update_table(&content, "timer", &timer);
+        fs::write(&path, content).map_err(|e| ConfigError::Io(path, e))
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.timer.validate()
+    }
+}
+
+fn key_of(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if line.starts_with('#') || line.starts_with('[') {
+        return None;
+    }
+    line.split('=')
+        .next()
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+}
+
+fn table_of(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.starts_with('[') {
+        Some(line.trim_matches(|c| c == '[' || c == ']').trim())
+    } else {
+        None
+    }
+}
+
+// set `key = value` lines of a flat table in a toml document, line by line, a trailing
+// comment is kept, keys not in the document go to the end of the table
+fn update_table(content: &str, table: &str, values: &str) -> String {
+    let values: Vec<(&str, &str)> = values
+        .lines()
+        .filter_map(|line| Some((key_of(line)?, line.trim())))
+        .collect();
+    let mut written = vec![false; values.len()];
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = None;
+    // where the keys not written yet go
+    let mut table_end = None;
+
+    for line in content.lines() {
+        if let Some(name) = table_of(line) {
+            current = Some(name);
+        } else if current == Some(table) {
+            if let Some(idx) = key_of(line).and_then(|k| values.iter().position(|v| v.0 == k)) {
+                let mut new = values[idx].1.to_owned();
+                if let Some(comment) = line.find(" #").map(|at| &line[at..]) {
+                    new.push_str(comment);
+                }
+                written[idx] = true;
+                lines.push(new);
+                table_end = Some(lines.len());
+                continue;
+            }
+        }
+        lines.push(line.to_owned());
+        if current == Some(table) && !line.trim().is_empty() {
+            table_end = Some(lines.len());
+        }
+    }
+
+    let missing = values
+        .iter()
+        .zip(&written)
+        .filter(|(_, written)| !**written)
+        .map(|(v, _)| v.1.to_owned());
+    match table_end {
+        Some(end) => {
+            let tail = lines.split_off(end);
+            lines.extend(missing);
+            lines.extend(tail);
+        }
+        None => {
+            if matches!(lines.last(), Some(l) if !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{}]", table));
+            lines.extend(missing);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// (de)serialize a duration of seconds as minutes
+pub mod minutes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(secs: &u64, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(secs / 60)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<u64, D::Error> {
+        u64::deserialize(d).map(|mins| mins.saturating_mul(60))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_keeps_comments_and_other_tables() {
+        let content = "# my config\n\n[timer]\n# half an hour\ntask_duration = 30 # focus\nauto_start_breaks = true\n\n[notify]\nsound = false\n";
+        let updated = update_table(
+            content,
+            "timer",
+            "task_duration = 50\nauto_start_breaks = false\n",
+        );
+        assert_eq!(
+            updated,
+            "# my config\n\n[timer]\n# half an hour\ntask_duration = 50 # focus\nauto_start_breaks = false\n\n[notify]\nsound = false\n"
+        );
+    }
+
+    #[test]
+    fn update_adds_missing_keys_to_the_table() {
+        let content = "[timer]\ntask_duration = 30\n\n[hooks]\n";
+        let updated = update_table(
+            content,
+            "timer",
+            "task_duration = 30\nlong_break_interval = 4\n",
+        );
+        assert_eq!(
+            updated,
+            "[timer]\ntask_duration = 30\nlong_break_interval = 4\n\n[hooks]\n"
+        );
+    }
+
+    #[test]
+    fn update_adds_missing_table() {
+        let content = "# nothing yet";
+        let updated = update_table(content, "timer", "task_duration = 30\n");
+        assert_eq!(updated, "# nothing yet\n\n[timer]\ntask_duration = 30\n");
+    }
+
+    #[test]
+    fn updated_timer_loads_back() {
+        let content = "# mine\n[timer]\ntask_duration = 30\n";
+        let timer = TomatoConfig {
+            task_duration: 45 * 60,
+            auto_start_focus: true,
+            ..Default::default()
+        };
+        let updated = update_table(content, "timer", &toml::to_string(&timer).unwrap());
+        let config: Config = toml::from_str(&updated).unwrap();
+        assert_eq!(config.timer.task_duration, 45 * 60);
+        assert!(config.timer.auto_start_focus);
+        assert!(updated.starts_with("# mine\n"));
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+
+use diesel::r2d2::PoolError;
+
+#[derive(Debug)]
+pub enum DbError {
+    // database file can't be opened or migrated
+    Init(String),
+    // no connection available in the pool
+    Pool(PoolError),
+    Query(diesel::result::Error),
+}
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DbError::Init(e) => write!(f, "can't open database: {}", e),
+            DbError::Pool(e) => write!(f, "database is busy: {}", e),
+            DbError::Query(e) => write!(f, "database error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DbError {}
+
+impl From<PoolError> for DbError {
+    fn from(e: PoolError) -> Self {
+        DbError::Pool(e)
+    }
+}
+
+impl From<diesel::result::Error> for DbError {
+    fn from(e: diesel::result::Error) -> Self {
+        DbError::Query(e)
+    }
+}
@@ -1,141 +1,526 @@
+mod error;
 mod models;
 pub mod schema;
 use diesel::dsl::sql;
+pub use error::DbError;
 pub use models::*;
 
+use diesel::connection::SimpleConnection;
 use diesel::prelude::*;
-use diesel::r2d2::{Builder, ConnectionManager, Pool, PooledConnection};
+use diesel::r2d2::{Builder, ConnectionManager, CustomizeConnection, Pool, PooledConnection};
+use diesel::sql_types::{BigInt, Integer};
 use diesel::sqlite::SqliteConnection;
 use dotenv::dotenv;
 use std::env;
+use std::fs;
+use std::time::Duration;
 
 use tui::style::Color;
 
+// migrations/ is compiled into the binary and run on the first connection
+embed_migrations!();
+
+const DB_DIR: &str = "tomato";
+const DB_FILE: &str = "tomato.db";
+
+/// `DATABASE_URL` if set, otherwise `$XDG_DATA_HOME/tomato/tomato.db` on Linux
+fn database_url() -> Result<String, String> {
+    dotenv().ok();
+    if let Ok(url) = env::var("DATABASE_URL") {
+        return Ok(url);
+    }
+    let dir = dirs::data_dir()
+        .ok_or_else(|| "can't determine the data directory".to_owned())?
+        .join(DB_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    Ok(dir.join(DB_FILE).to_string_lossy().into_owned())
+}
+
+// foreign keys are off by default in SQLite, and the setting is per connection
+#[derive(Debug)]
+struct EnableForeignKeys;
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for EnableForeignKeys {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.batch_execute("PRAGMA foreign_keys = ON")
+            .map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+fn init_pool() -> Result<Pool<ConnectionManager<SqliteConnection>>, String> {
+    let pool = Builder::new()
+        .max_size(5)
+        // r2d2 waits 30s by default, too long to tell users something is wrong
+        .connection_timeout(Duration::from_secs(5))
+        .connection_customizer(Box::new(EnableForeignKeys))
+        .build(ConnectionManager::new(database_url()?))
+        .map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    embedded_migrations::run(&*conn).map_err(|e| e.to_string())?;
+    Ok(pool)
+}
+
 lazy_static::lazy_static! {
-    pub static ref DB_POOL: Pool<ConnectionManager<SqliteConnection>> = {
-        dotenv().ok();
-        let db_file = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-        Builder::new()
-            .max_size(5)
-            .build(ConnectionManager::new(db_file))
-            .expect("Failed to connect database")
-    };
+    // keep the init error, every later access reports it again
+    static ref DB_POOL: Result<Pool<ConnectionManager<SqliteConnection>>, String> = init_pool();
 }
 
-fn conn() -> PooledConnection<ConnectionManager<SqliteConnection>> {
-    DB_POOL.get().expect("Failed to get connection")
+fn conn() -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, DbError> {
+    let pool = DB_POOL.as_ref().map_err(|e| DbError::Init(e.clone()))?;
+    Ok(pool.get()?)
 }
 
 pub struct DbUtils;
 
 impl DbUtils {
-    pub fn create_new_inventory(name: &str, color: i32) -> Inventory {
+    pub fn create_new_inventory(name: &str, color: i32) -> Result<Inventory, DbError> {
         use schema::inventory::dsl;
-        let conn = conn();
-        conn.transaction(|| {
+        let conn = conn()?;
+        let inv = conn.transaction(|| {
             diesel::insert_into(dsl::inventory)
                 .values((dsl::name.eq(name), dsl::color.eq(color)))
-                .execute(&conn)
-                .unwrap();
+                .execute(&conn)?;
             dsl::inventory
                 .find(sql("last_insert_rowid()"))
                 .get_result(&conn)
-        })
-        .unwrap()
+        })?;
+        Ok(inv)
     }
 
-    pub fn delete_inventory(id: i32) {
-        use schema::inventory::dsl::inventory;
-        use schema::tasks::dsl::{inventory_id as task_inv_id, tasks};
-        use schema::tomatos::dsl::{inventory_id as tomatos_inv_id, tomatos};
-        let conn = conn();
-        diesel::delete(inventory.find(id)).execute(&conn).unwrap();
-        diesel::delete(tasks.filter(task_inv_id.eq(id)))
-            .execute(&conn)
-            .unwrap();
-        diesel::delete(tomatos.filter(tomatos_inv_id.eq(id)))
-            .execute(&conn)
-            .unwrap();
+    /// tasks, tomatos and goals of the inventory are deleted by `ON DELETE CASCADE`,
+    /// all the removed rows are returned
+    pub fn delete_inventory(id: i32) -> Result<Purged, DbError> {
+        use schema::{goals, inventory, tasks, tomatos};
+        let conn = conn()?;
+        let purged = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let inv: Inventory = inventory::table.find(id).get_result(&conn)?;
+            let task_rows: Vec<Task> = tasks::table
+                .filter(tasks::inventory_id.eq(id))
+                .load(&conn)?;
+            let task_ids: Vec<_> = task_rows.iter().map(|t| t.id).collect();
+            let tomato_rows = tomatos::table
+                .filter(
+                    tomatos::inventory_id
+                        .eq(id)
+                        .or(tomatos::task_id.eq_any(task_ids)),
+                )
+                .load(&conn)?;
+            let goal_rows = goals::table
+                .filter(goals::inventory_id.eq(id))
+                .load(&conn)?;
+            diesel::delete(inventory::table.find(id)).execute(&conn)?;
+            Ok(Purged {
+                inventory: Some(inv),
+                tasks: task_rows,
+                tomatos: tomato_rows,
+                goals: goal_rows,
+            })
+        })?;
+        Ok(purged)
     }
 
-    pub fn create_new_task(inventory_id: i32, name: &str, notes: Option<&str>) -> Task {
-        let conn = conn();
+    pub fn create_new_task(
+        inventory_id: i32,
+        name: &str,
+        notes: Option<&str>,
+    ) -> Result<Task, DbError> {
+        let conn = conn()?;
         let create_at = chrono::Local::now().timestamp();
         use schema::tasks::dsl;
-        conn.transaction(|| {
+        let task = conn.transaction(|| {
             diesel::insert_into(dsl::tasks)
                 .values(TaskRow {
                     inventory_id,
                     name,
-                    spent_minutes: 0,
+                    spent_seconds: 0,
                     create_at,
                     notes,
                 })
-                .execute(&conn)
-                .unwrap();
+                .execute(&conn)?;
             dsl::tasks
                 .find(sql("last_insert_rowid()"))
                 .get_result(&conn)
-        })
-        .unwrap()
+        })?;
+        Ok(task)
     }
 
-    pub fn delete_task(id: i32) {
-        use schema::tasks::dsl::tasks;
-        use schema::tomatos::dsl::{task_id, tomatos};
-        let conn = conn();
-        diesel::delete(tasks.find(id)).execute(&conn).unwrap();
-        diesel::delete(tomatos.filter(task_id.eq(id)))
-            .execute(&conn)
-            .unwrap();
+    /// tomatos of the task are deleted by `ON DELETE CASCADE`,
+    /// all the removed rows are returned
+    pub fn delete_task(id: i32) -> Result<Purged, DbError> {
+        use schema::{tasks, tomatos};
+        let conn = conn()?;
+        let purged = conn.transaction::<_, diesel::result::Error, _>(|| {
+            let task: Task = tasks::table.find(id).get_result(&conn)?;
+            let tomato_rows = tomatos::table.filter(tomatos::task_id.eq(id)).load(&conn)?;
+            diesel::delete(tasks::table.find(id)).execute(&conn)?;
+            Ok(Purged {
+                inventory: None,
+                tasks: vec![task],
+                tomatos: tomato_rows,
+                goals: Vec::new(),
+            })
+        })?;
+        Ok(purged)
     }
 
-    pub fn create_new_tomato(tomato: Tomato) {
-        let conn = conn();
-        diesel::insert_into(schema::tomatos::table)
-            .values(tomato)
-            .execute(&conn)
-            .unwrap();
+    /// (tasks, tomatos) deleted along with the inventory
+    pub fn inventory_usage(id: i32) -> Result<(i64, i64), DbError> {
+        use schema::{tasks, tomatos};
+        let conn = conn()?;
+        let task_ids = tasks::table
+            .select(tasks::id)
+            .filter(tasks::inventory_id.eq(id));
+        let task_count = task_ids.count().get_result(&conn)?;
+        let tomato_count = tomatos::table
+            .filter(
+                tomatos::inventory_id
+                    .eq(id)
+                    .or(tomatos::task_id.eq_any(task_ids)),
+            )
+            .count()
+            .get_result(&conn)?;
+        Ok((task_count, tomato_count))
     }
 
-    pub fn update_task_spent(task_id: i32, delta_spent: i64) {
-        let conn = conn();
+    /// tomatos deleted along with the task
+    pub fn task_usage(id: i32) -> Result<i64, DbError> {
+        use schema::tomatos;
+        let conn = conn()?;
+        Ok(tomatos::table
+            .filter(tomatos::task_id.eq(id))
+            .count()
+            .get_result(&conn)?)
+    }
+
+    /// insert purged rows back with their original ids
+    pub fn revive(purged: &Purged) -> Result<(), DbError> {
+        use schema::{goals, inventory, tasks, tomatos};
+        let conn = conn()?;
+        conn.transaction(|| {
+            if let Some(inv) = &purged.inventory {
+                diesel::insert_into(inventory::table)
+                    .values((
+                        inventory::id.eq(inv.id),
+                        inventory::name.eq(&inv.name),
+                        inventory::color.eq(i32::from(inv.color)),
+                        inventory::archived_at.eq(inv.archived_at),
+                    ))
+                    .execute(&conn)?;
+            }
+            // no batch insert for sqlite
+            for task in &purged.tasks {
+                diesel::insert_into(tasks::table)
+                    .values(task)
+                    .execute(&conn)?;
+            }
+            for tomato in &purged.tomatos {
+                diesel::insert_into(tomatos::table)
+                    .values(tomato)
+                    .execute(&conn)?;
+            }
+            for goal in &purged.goals {
+                diesel::insert_into(goals::table)
+                    .values(goal)
+                    .execute(&conn)?;
+            }
+            Ok::<_, diesel::result::Error>(())
+        })?;
+        Ok(())
+    }
+
+    /// the tomato and the time it adds to its task are saved together
+    pub fn create_new_tomato(tomato: Tomato, delta_tomatos: i32) -> Result<(), DbError> {
         use schema::tasks::dsl::*;
-        diesel::update(tasks.find(task_id))
-            .set(spent_minutes.eq(spent_minutes + delta_spent))
-            .execute(&conn)
-            .unwrap();
+        let conn = conn()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::update(tasks.find(tomato.task_id))
+                .set((
+                    spent_seconds.eq(spent_seconds + tomato.focused_seconds()),
+                    spent_tomatos.eq(spent_tomatos + delta_tomatos),
+                ))
+                .execute(&conn)?;
+            diesel::insert_into(schema::tomatos::table)
+                .values(tomato)
+                .execute(&conn)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    pub fn all_goals() -> Result<Vec<Goal>, DbError> {
+        use schema::goals::table;
+        let conn = conn()?;
+        Ok(table.load(&conn)?)
+    }
+
+    /// one goal per inventory, a goal of zeros is removed
+    pub fn set_goal(goal: &Goal) -> Result<(), DbError> {
+        use schema::goals::dsl::*;
+        let conn = conn()?;
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            match goal.inventory_id {
+                Some(inv_id) => {
+                    diesel::delete(goals.filter(inventory_id.eq(inv_id))).execute(&conn)?
+                }
+                None => diesel::delete(goals.filter(inventory_id.is_null())).execute(&conn)?,
+            };
+            if goal.daily > 0 || goal.weekly > 0 {
+                diesel::insert_into(goals)
+                    .values((
+                        inventory_id.eq(goal.inventory_id),
+                        daily.eq(goal.daily),
+                        weekly.eq(goal.weekly),
+                    ))
+                    .execute(&conn)?;
+            }
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    /// None clears the saved session
+    pub fn save_session(saved: Option<&Session>) -> Result<(), DbError> {
+        use schema::session::table;
+        let conn = conn()?;
+        match saved {
+            Some(saved) => diesel::replace_into(table).values(saved).execute(&conn)?,
+            None => diesel::delete(table).execute(&conn)?,
+        };
+        Ok(())
+    }
+
+    pub fn load_session() -> Result<Option<Session>, DbError> {
+        use schema::session::table;
+        let conn = conn()?;
+        Ok(table.first(&conn).optional()?)
+    }
+
+    /// the replaced values are returned, to edit them back
+    pub fn edit_inventory(inv: &EditInventory) -> Result<EditInventory, DbError> {
+        use schema::inventory::table;
+        let conn = conn()?;
+        let old = conn.transaction::<Inventory, diesel::result::Error, _>(|| {
+            let old = table.find(inv.id).get_result(&conn)?;
+            diesel::update(table.find(inv.id)).set(inv).execute(&conn)?;
+            Ok(old)
+        })?;
+        let color = old.color.into();
+        Ok(EditInventory {
+            id: old.id,
+            name: inv.name.as_ref().map(|_| old.name),
+            color: inv.color.map(|_| color),
+        })
+    }
+
+    /// the replaced values are returned, to edit them back
+    pub fn edit_task(task: &EditTask) -> Result<EditTask, DbError> {
+        use schema::tasks::table;
+        let conn = conn()?;
+        let old = conn.transaction::<Task, diesel::result::Error, _>(|| {
+            let old = table.find(task.id).get_result(&conn)?;
+            diesel::update(table.find(task.id))
+                .set(task)
+                .execute(&conn)?;
+            Ok(old)
+        })?;
+        let (estimate, completed_at) = (old.estimate, old.completed_at);
+        let (name, notes) = (old.name, old.notes);
+        Ok(EditTask {
+            id: old.id,
+            name: task.name.as_ref().map(|_| name),
+            estimate: task.estimate.map(|_| estimate),
+            completed_at: task.completed_at.map(|_| completed_at),
+            notes: task.notes.as_ref().map(|_| notes),
+        })
+    }
+
+    /// inventories not archived
+    pub fn all_inventory() -> Result<Vec<Inventory>, DbError> {
+        use schema::inventory::dsl::*;
+        let conn = conn()?;
+        Ok(inventory
+            .filter(archived_at.is_null())
+            .load::<Inventory>(&conn)?)
+    }
+
+    /// tasks not archived
+    pub fn all_task_groupby(invs: &[Inventory]) -> Result<Vec<Vec<Task>>, DbError> {
+        use schema::tasks::dsl::archived_at;
+        let conn = conn()?;
+        Ok(Task::belonging_to(invs)
+            .filter(archived_at.is_null())
+            .get_results::<Task>(&conn)?
+            .grouped_by(invs))
+    }
+
+    pub fn archive_inventory(id: i32) -> Result<Inventory, DbError> {
+        use schema::inventory::dsl::{archived_at, inventory};
+        let conn = conn()?;
+        let now = chrono::Local::now().timestamp();
+        let archived = conn.transaction(|| {
+            diesel::update(inventory.find(id))
+                .set(archived_at.eq(Some(now)))
+                .execute(&conn)?;
+            inventory.find(id).get_result(&conn)
+        })?;
+        Ok(archived)
+    }
+
+    pub fn archive_task(id: i32) -> Result<Task, DbError> {
+        use schema::tasks::dsl::{archived_at, tasks};
+        let conn = conn()?;
+        let now = chrono::Local::now().timestamp();
+        let archived = conn.transaction(|| {
+            diesel::update(tasks.find(id))
+                .set(archived_at.eq(Some(now)))
+                .execute(&conn)?;
+            tasks.find(id).get_result(&conn)
+        })?;
+        Ok(archived)
+    }
+
+    /// the restored inventory and its tasks not archived
+    pub fn restore_inventory(id: i32) -> Result<(Inventory, Vec<Task>), DbError> {
+        use schema::inventory::dsl::{archived_at, inventory};
+        let conn = conn()?;
+        let inv: Inventory = conn.transaction(|| {
+            diesel::update(inventory.find(id))
+                .set(archived_at.eq(None::<i64>))
+                .execute(&conn)?;
+            inventory.find(id).get_result(&conn)
+        })?;
+        let tasks = Task::belonging_to(&inv)
+            .filter(schema::tasks::dsl::archived_at.is_null())
+            .load::<Task>(&conn)?;
+        Ok((inv, tasks))
     }
 
-    pub fn edit_inventory(inv: &EditInventory) {
-        let conn = conn();
-        diesel::update(schema::inventory::table.find(inv.id))
-            .set(inv)
-            .execute(&conn)
-            .unwrap();
+    pub fn restore_task(id: i32) -> Result<Task, DbError> {
+        use schema::tasks::dsl::{archived_at, tasks};
+        let conn = conn()?;
+        let task = conn.transaction(|| {
+            diesel::update(tasks.find(id))
+                .set(archived_at.eq(None::<i64>))
+                .execute(&conn)?;
+            tasks.find(id).get_result(&conn)
+        })?;
+        Ok(task)
     }
 
-    pub fn edit_task(task: &EditTask) {
-        let conn = conn();
-        diesel::update(schema::tasks::table.find(task.id))
-            .set(task)
-            .execute(&conn)
-            .unwrap();
+    /// archived inventories
+    pub fn archived_inventory() -> Result<Vec<Inventory>, DbError> {
+        use schema::inventory::dsl::*;
+        let conn = conn()?;
+        Ok(inventory
+            .filter(archived_at.is_not_null())
+            .order(archived_at.desc())
+            .load::<Inventory>(&conn)?)
     }
 
-    pub fn all_inventory() -> Vec<Inventory> {
-        let conn = conn();
-        schema::inventory::dsl::inventory
-            .load::<Inventory>(&conn)
-            .unwrap()
+    /// archived tasks whose inventory is not archived
+    pub fn archived_tasks() -> Result<Vec<(Task, Inventory)>, DbError> {
+        use schema::{inventory, tasks};
+        let conn = conn()?;
+        Ok(tasks::table
+            .inner_join(inventory::table)
+            .filter(tasks::archived_at.is_not_null())
+            .filter(inventory::archived_at.is_null())
+            .order(tasks::archived_at.desc())
+            .load::<(Task, Inventory)>(&conn)?)
     }
 
-    pub fn all_task_groupby(invs: &[Inventory]) -> Vec<Vec<Task>> {
-        let conn = conn();
-        Task::belonging_to(invs)
-            .get_results::<Task>(&conn)
-            .unwrap()
-            .grouped_by(invs)
+    /// focus time per local date since `since`, ordered by date
+    pub fn focus_per_day(since: i64) -> Result<Vec<DayFocus>, DbError> {
+        let conn = conn()?;
+        let days = diesel::sql_query(
+            "SELECT date(start_time, 'unixepoch', 'localtime') AS day, \
+                SUM(end_time - start_time - paused_seconds) AS seconds, \
+                SUM(outcome = 0) AS count, COUNT(*) AS sessions \
+             FROM tomatos WHERE start_time >= ? \
+             GROUP BY day ORDER BY day",
+        )
+        .bind::<BigInt, _>(since)
+        .load(&conn)?;
+        Ok(days)
+    }
+
+    /// focus time per inventory since `since`, the most focused first
+    pub fn focus_per_inventory(since: i64) -> Result<Vec<InventoryFocus>, DbError> {
+        let conn = conn()?;
+        let invs = diesel::sql_query(
+            "SELECT inventory.name, inventory.color, \
+                SUM(end_time - start_time - paused_seconds) AS seconds, \
+                SUM(outcome = 0) AS count \
+             FROM tomatos JOIN inventory ON inventory.id = tomatos.inventory_id \
+             WHERE start_time >= ? \
+             GROUP BY inventory.id ORDER BY seconds DESC",
+        )
+        .bind::<BigInt, _>(since)
+        .load(&conn)?;
+        Ok(invs)
+    }
+
+    /// estimated tasks with tomatos started since `since`
+    pub fn estimate_accuracy(since: i64) -> Result<EstimateAccuracy, DbError> {
+        let conn = conn()?;
+        let accuracy = diesel::sql_query(
+            "SELECT COUNT(*) AS tasks, \
+                COALESCE(SUM(spent_tomatos <= estimate), 0) AS within, \
+                COALESCE(SUM(spent_tomatos), 0) AS spent, \
+                COALESCE(SUM(estimate), 0) AS estimated \
+             FROM tasks WHERE estimate > 0 \
+             AND id IN (SELECT task_id FROM tomatos WHERE start_time >= ?)",
+        )
+        .bind::<BigInt, _>(since)
+        .get_result(&conn)?;
+        Ok(accuracy)
+    }
+
+    /// completed tomatos per inventory started since `week`, and since `today` among them,
+    /// days go by start time as in the statistics
+    pub fn completed_per_inventory(week: i64, today: i64) -> Result<Vec<GoalCount>, DbError> {
+        let conn = conn()?;
+        let counts = diesel::sql_query(
+            "SELECT inventory_id, SUM(start_time >= ?) AS today, COUNT(*) AS week \
+             FROM tomatos WHERE outcome = 0 AND start_time >= ? \
+             GROUP BY inventory_id",
+        )
+        .bind::<BigInt, _>(today)
+        .bind::<BigInt, _>(week)
+        .load(&conn)?;
+        Ok(counts)
+    }
+
+    /// ids of the tasks with the latest tomatos, the most recent first
+    pub fn recent_task_ids(limit: i32) -> Result<Vec<i32>, DbError> {
+        let conn = conn()?;
+        let recent: Vec<RecentTask> = diesel::sql_query(
+            "SELECT task_id FROM tomatos \
+             GROUP BY task_id ORDER BY MAX(start_time) DESC LIMIT ?",
+        )
+        .bind::<Integer, _>(limit)
+        .load(&conn)?;
+        Ok(recent.into_iter().map(|r| r.task_id).collect())
+    }
+
+    /// tomatos started in [start, end), ordered by start time
+    pub fn tomatos_between(start: i64, end: i64) -> Result<Vec<TomatoDetail>, DbError> {
+        let conn = conn()?;
+        let tomatos = diesel::sql_query(
+            "SELECT start_time, end_time, outcome, \
+                COALESCE(tasks.name, '') AS task, \
+                COALESCE(inventory.name, '') AS inventory, \
+                COALESCE(inventory.color, 0) AS color \
+             FROM tomatos \
+             LEFT JOIN tasks ON tasks.id = tomatos.task_id \
+             LEFT JOIN inventory ON inventory.id = tomatos.inventory_id \
+             WHERE start_time >= ? AND start_time < ? \
+             ORDER BY start_time",
+        )
+        .bind::<BigInt, _>(start)
+        .bind::<BigInt, _>(end)
+        .load(&conn)?;
+        Ok(tomatos)
     }
 }
 
@@ -146,6 +531,15 @@ const RGB: i32 = 2;
 #[derive(Default, Clone, Copy)]
 pub struct DbColor(i32);
 
+impl DbColor {
+    pub const DARK_GRAY: DbColor = DbColor(9);
+
+    // one of the 256 terminal colors
+    pub const fn indexed(i: u8) -> Self {
+        DbColor(INDEX << 24 | i as i32)
+    }
+}
+
 impl From<i32> for DbColor {
     fn from(i: i32) -> Self {
         DbColor(i)
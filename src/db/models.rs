@@ -1,5 +1,24 @@
-use super::schema::{inventory, tasks, tomatos};
+use super::schema::{goals, inventory, session, tasks, tomatos};
 use super::DbColor;
+use diesel::sql_types::{BigInt, Integer, Text};
+
+// how a focus session ended, stored as an integer in tomatos.outcome
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Completed = 0,
+    Interrupted = 1,
+    Skipped = 2,
+}
+
+impl From<i32> for Outcome {
+    fn from(i: i32) -> Self {
+        match i {
+            1 => Outcome::Interrupted,
+            2 => Outcome::Skipped,
+            _ => Outcome::Completed,
+        }
+    }
+}
 
 #[derive(Insertable)]
 pub struct Tomato {
@@ -7,15 +26,24 @@ pub struct Tomato {
     pub task_id: i32,
     pub start_time: i64,
     pub end_time: i64,
+    pub outcome: i32,
+    pub paused_seconds: i64,
+}
+
+impl Tomato {
+    pub fn focused_seconds(&self) -> i64 {
+        self.end_time - self.start_time - self.paused_seconds
+    }
 }
 
-#[derive(Queryable, Identifiable, Default)]
+#[derive(Queryable, Identifiable, Default, Clone)]
 #[table_name = "inventory"]
 pub struct Inventory {
     pub id: i32,
     pub name: String,
     #[diesel(deserialize_as = "i32")]
     pub color: DbColor,
+    pub archived_at: Option<i64>,
 }
 
 #[derive(Default)]
@@ -24,7 +52,7 @@ pub struct NewInventory {
     pub color: i32,
 }
 
-#[derive(Default, AsChangeset)]
+#[derive(Default, Clone, AsChangeset)]
 #[table_name = "inventory"]
 pub struct EditInventory {
     pub id: i32,
@@ -32,22 +60,43 @@ pub struct EditInventory {
     pub color: Option<i32>,
 }
 
-#[derive(Default, AsChangeset)]
+#[derive(Default, Clone, AsChangeset)]
 #[table_name = "tasks"]
 pub struct EditTask {
     pub id: i32,
     pub name: Option<String>,
+    // Some(None) clears the estimate
+    pub estimate: Option<Option<i32>>,
+    // Some(None) opens the task again
+    pub completed_at: Option<Option<i64>>,
+    // Some(None) clears the notes
+    pub notes: Option<Option<String>>,
 }
 
-#[derive(Queryable, Identifiable, Associations, Default)]
+#[derive(Queryable, Insertable, Identifiable, Associations, Default, Clone)]
 #[belongs_to(Inventory)]
+#[table_name = "tasks"]
 pub struct Task {
     pub id: i32,
     pub inventory_id: i32,
     pub name: String,
-    pub spent_minutes: i64,
+    pub spent_seconds: i64,
     pub create_at: i64,
     pub notes: Option<String>,
+    pub archived_at: Option<i64>,
+    // tomatoes expected to take
+    pub estimate: Option<i32>,
+    // completed tomatoes
+    pub spent_tomatos: i32,
+    // None while open
+    pub completed_at: Option<i64>,
+}
+
+impl Task {
+    // negative if over the estimate
+    pub fn tomatos_left(&self) -> Option<i32> {
+        self.estimate.map(|estimate| estimate - self.spent_tomatos)
+    }
 }
 
 #[derive(Insertable)]
@@ -55,14 +104,146 @@ pub struct Task {
 pub struct TaskRow<'a> {
     pub inventory_id: i32,
     pub name: &'a str,
-    pub spent_minutes: i64,
+    pub spent_seconds: i64,
     pub create_at: i64,
     pub notes: Option<&'a str>,
 }
 
+// a tomato row with its id
+#[derive(Queryable, Insertable)]
+#[table_name = "tomatos"]
+pub struct TomatoRecord {
+    pub id: i32,
+    pub inventory_id: i32,
+    pub task_id: i32,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub outcome: i32,
+    pub paused_seconds: i64,
+}
+
+// rows removed by a purge, enough to put them back as they were
+pub struct Purged {
+    // None if only a task is purged
+    pub inventory: Option<Inventory>,
+    pub tasks: Vec<Task>,
+    pub tomatos: Vec<TomatoRecord>,
+    pub goals: Vec<Goal>,
+}
+
+// completed tomatoes to reach, 0 for no goal
+#[derive(Queryable, Insertable, Default, Clone)]
+#[table_name = "goals"]
+pub struct Goal {
+    pub id: i32,
+    // None for all inventories together
+    pub inventory_id: Option<i32>,
+    pub daily: i32,
+    pub weekly: i32,
+}
+
+// completed tomatos of an inventory toward its goals
+#[derive(QueryableByName)]
+pub struct GoalCount {
+    #[sql_type = "Integer"]
+    pub inventory_id: i32,
+    #[sql_type = "BigInt"]
+    pub today: i64,
+    #[sql_type = "BigInt"]
+    pub week: i64,
+}
+
+// a task with tomatos, for the latest ones
+#[derive(QueryableByName)]
+pub struct RecentTask {
+    #[sql_type = "Integer"]
+    pub task_id: i32,
+}
+
+// the countdown as it was when saved, durations in seconds
+#[derive(Queryable, Insertable, Clone)]
+#[table_name = "session"]
+pub struct Session {
+    // always 0, there is one session at most
+    pub id: i32,
+    // index in the focus and break cycle
+    pub phase_idx: i32,
+    pub long_break_interval: i32,
+    pub duration: i64,
+    pub left_seconds: i64,
+    pub paused: bool,
+    pub task_id: Option<i32>,
+    pub started_at: Option<i64>,
+    pub paused_seconds: i64,
+    // unix time, to tell how long the app was closed
+    pub saved_at: i64,
+}
+
 #[derive(Default)]
 pub struct NewTask {
     pub inventory_id: i32,
     pub name: String,
     pub notes: Option<String>,
 }
+
+// aggregated tomatos of a local date
+#[derive(QueryableByName)]
+pub struct DayFocus {
+    #[sql_type = "Text"]
+    pub day: String,
+    #[sql_type = "BigInt"]
+    pub seconds: i64,
+    // completed ones
+    #[sql_type = "BigInt"]
+    pub count: i64,
+    // all focus sessions, interrupted and skipped included
+    #[sql_type = "BigInt"]
+    pub sessions: i64,
+}
+
+// aggregated tomatos of an inventory
+#[derive(QueryableByName)]
+pub struct InventoryFocus {
+    #[sql_type = "Text"]
+    pub name: String,
+    #[sql_type = "Integer"]
+    #[diesel(deserialize_as = "i32")]
+    pub color: DbColor,
+    #[sql_type = "BigInt"]
+    pub seconds: i64,
+    #[sql_type = "BigInt"]
+    pub count: i64,
+}
+
+// estimated tasks worked on in a range, tomatoes are completed ones
+#[derive(QueryableByName, Default)]
+pub struct EstimateAccuracy {
+    #[sql_type = "BigInt"]
+    pub tasks: i64,
+    // tasks not over the estimate
+    #[sql_type = "BigInt"]
+    pub within: i64,
+    #[sql_type = "BigInt"]
+    pub spent: i64,
+    #[sql_type = "BigInt"]
+    pub estimated: i64,
+}
+
+// a tomato with names of its task and inventory
+#[derive(QueryableByName)]
+pub struct TomatoDetail {
+    #[sql_type = "BigInt"]
+    pub start_time: i64,
+    #[sql_type = "BigInt"]
+    pub end_time: i64,
+    #[sql_type = "Text"]
+    pub task: String,
+    #[sql_type = "Text"]
+    pub inventory: String,
+    #[sql_type = "Integer"]
+    #[diesel(deserialize_as = "i32")]
+    pub color: DbColor,
+    #[sql_type = "Integer"]
+    #[diesel(deserialize_as = "i32")]
+    pub outcome: Outcome,
+}
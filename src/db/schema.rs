@@ -1,8 +1,18 @@
+table! {
+    goals (id) {
+        id -> Integer,
+        inventory_id -> Nullable<Integer>,
+        daily -> Integer,
+        weekly -> Integer,
+    }
+}
+
 table! {
     inventory (id) {
         id -> Integer,
         name -> Text,
         color -> Integer,
+        archived_at -> Nullable<BigInt>,
     }
 }
 
@@ -11,9 +21,13 @@ table! {
         id -> Integer,
         inventory_id -> Integer,
         name -> Text,
-        spent_minutes -> BigInt,
+        spent_seconds -> BigInt,
         create_at -> BigInt,
         notes -> Nullable<Text>,
+        archived_at -> Nullable<BigInt>,
+        estimate -> Nullable<Integer>,
+        spent_tomatos -> Integer,
+        completed_at -> Nullable<BigInt>,
     }
 }
 
@@ -24,11 +38,35 @@ table! {
         task_id -> Integer,
         start_time -> BigInt,
         end_time -> BigInt,
+        outcome -> Integer,
+        paused_seconds -> BigInt,
     }
 }
 
+table! {
+    session (id) {
+        id -> Integer,
+        phase_idx -> Integer,
+        long_break_interval -> Integer,
+        duration -> BigInt,
+        left_seconds -> BigInt,
+        paused -> Bool,
+        task_id -> Nullable<Integer>,
+        started_at -> Nullable<BigInt>,
+        paused_seconds -> BigInt,
+        saved_at -> BigInt,
+    }
+}
+
+joinable!(goals -> inventory (inventory_id));
+joinable!(tasks -> inventory (inventory_id));
+joinable!(tomatos -> inventory (inventory_id));
+joinable!(tomatos -> tasks (task_id));
+
 allow_tables_to_appear_in_same_query!(
+    goals,
     inventory,
+    session,
     tasks,
     tomatos,
 );
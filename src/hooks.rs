@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+/// Shell commands run on phase changes, by `sh -c`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus_start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus_end: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub break_start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub break_end: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    FocusStart,
+    FocusEnd,
+    BreakStart,
+    BreakEnd,
+}
+
+impl HookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::FocusStart => "focus-start",
+            HookEvent::FocusEnd => "focus-end",
+            HookEvent::BreakStart => "break-start",
+            HookEvent::BreakEnd => "break-end",
+        }
+    }
+
+    // shown when the hook fails
+    pub fn label(&self) -> &'static str {
+        match self {
+            HookEvent::FocusStart => "focus-start hook",
+            HookEvent::FocusEnd => "focus-end hook",
+            HookEvent::BreakStart => "break-start hook",
+            HookEvent::BreakEnd => "break-end hook",
+        }
+    }
+}
+
+// trace: TomatoModel -> App(append task names) -> IO process
+pub struct Hook {
+    pub event: HookEvent,
+    // focus, short-break or long-break
+    pub phase: &'static str,
+    // planned seconds of the phase
+    pub duration: u64,
+    // seconds counted down so far, pauses excluded
+    pub elapsed: u64,
+    pub inventory: String,
+    pub task: String,
+}
+
+impl Hook {
+    pub fn command(&self, config: &HooksConfig) -> Option<Command> {
+        let script = match self.event {
+            HookEvent::FocusStart => &config.focus_start,
+            HookEvent::FocusEnd => &config.focus_end,
+            HookEvent::BreakStart => &config.break_start,
+            HookEvent::BreakEnd => &config.break_end,
+        }
+        .as_ref()?;
+
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(script)
+            .env("TOMATO_EVENT", self.event.name())
+            .env("TOMATO_PHASE", self.phase)
+            .env("TOMATO_DURATION", self.duration.to_string())
+            .env("TOMATO_ELAPSED", self.elapsed.to_string())
+            .env("TOMATO_INVENTORY", &self.inventory)
+            .env("TOMATO_TASK", &self.task);
+        Some(cmd)
+    }
+}
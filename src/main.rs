@@ -1,23 +1,84 @@
 #[macro_use]
 extern crate diesel;
+#[macro_use]
+extern crate diesel_migrations;
 
-use std::io;
-use termion::{raw::IntoRawMode, screen::AlternateScreen};
+use std::io::{self, Write};
+use std::{panic, thread};
+use termion::{
+    cursor,
+    raw::IntoRawMode,
+    screen::{AlternateScreen, ToMainScreen},
+};
 use tui::backend::TermionBackend;
 use tui::Terminal;
 
+mod cli;
+mod config;
 mod db;
 mod events;
+mod hooks;
 mod models;
+mod notifier;
 mod process;
 mod views;
+use config::Config;
+use db::DbUtils;
 use events::{Event, Events, Key};
 use models::AppBuilder;
+use process::PROCESS_THREAD;
 
 use futures::StreamExt;
+use structopt::StructOpt;
+
+/// Leave the alternate screen before a panic message is printed, otherwise it is gone
+/// with the screen. Raw mode is restored when the terminal is dropped during unwinding.
+fn set_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        if thread::current().name() == Some(PROCESS_THREAD) {
+            // the process worker reports its panics in notifications
+            return;
+        }
+        let mut stdout = io::stdout();
+        let _ = write!(stdout, "{}{}", ToMainScreen, cursor::Show);
+        let _ = stdout.flush();
+        // still in raw mode, return carriages manually
+        eprint!("{}\r\n", info.to_string().replace('\n', "\r\n"));
+    }));
+}
 
 #[tokio::main]
 async fn main() -> Result<(), io::Error> {
+    // arguments first, --help needs no config
+    let opt = cli::Opt::from_args();
+
+    // load config before entering raw mode, so errors are readable
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("tomato: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(cmd) = opt.cmd {
+        if let Err(e) = cli::run(cmd, config).await {
+            eprintln!("tomato: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // open database before entering raw mode too
+    let mut app = match AppBuilder::default().config(config).build() {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("tomato: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    set_panic_hook();
     let stdout = io::stdout().into_raw_mode()?;
     // let stdout = MouseTerminal::from(stdout);
     let stdout = AlternateScreen::from(stdout);
@@ -25,7 +86,6 @@ async fn main() -> Result<(), io::Error> {
     let mut terminal = Terminal::new(backend)?;
 
     let mut events = Events::new();
-    let mut app = AppBuilder::default().build();
 
     loop {
         tokio::select! {
@@ -56,5 +116,12 @@ async fn main() -> Result<(), io::Error> {
             views::ui::draw_app(f, &app);
         })?;
     }
+
+    // saved here rather than by the process worker, which is gone with main
+    let saved = DbUtils::save_session(app.session().as_ref());
+    drop(terminal);
+    if let Err(e) = saved {
+        eprintln!("tomato: can't save the countdown: {}", e);
+    }
     Ok(())
 }
@@ -0,0 +1,80 @@
+use crate::db::{DbError, DbUtils, Inventory, Task};
+
+pub enum ArchivedItem {
+    Inventory(Inventory),
+    // with the name of its inventory
+    Task(Task, String),
+}
+
+impl ArchivedItem {
+    /// archived inventories first, then archived tasks of living inventories
+    pub fn load_all() -> Result<Vec<ArchivedItem>, DbError> {
+        let mut items: Vec<_> = DbUtils::archived_inventory()?
+            .into_iter()
+            .map(ArchivedItem::Inventory)
+            .collect();
+        items.extend(
+            DbUtils::archived_tasks()?
+                .into_iter()
+                .map(|(task, inv)| ArchivedItem::Task(task, inv.name)),
+        );
+        Ok(items)
+    }
+}
+
+pub struct ArchiveModel {
+    pub items: Vec<ArchivedItem>,
+    pub selected: Option<usize>,
+}
+
+impl ArchiveModel {
+    pub fn new() -> Self {
+        ArchiveModel {
+            items: Vec::new(),
+            selected: None,
+        }
+    }
+
+    pub fn set_items(&mut self, items: Vec<ArchivedItem>) {
+        self.selected = if items.is_empty() { None } else { Some(0) };
+        self.items = items;
+    }
+
+    pub fn selected_item(&self) -> Option<&ArchivedItem> {
+        self.selected.and_then(|idx| self.items.get(idx))
+    }
+
+    pub fn next(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = Some(self.selected.map_or(0, |idx| (idx + 1) % self.items.len()));
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.items.is_empty() {
+            let last = self.items.len() - 1;
+            self.selected = Some(
+                self.selected
+                    .map_or(last, |idx| if idx == 0 { last } else { idx - 1 }),
+            );
+        }
+    }
+
+    // restored or purged
+    pub fn remove_inventory(&mut self, id: i32) {
+        self.retain(|item| !matches!(item, ArchivedItem::Inventory(inv) if inv.id == id));
+    }
+
+    pub fn remove_task(&mut self, id: i32) {
+        self.retain(|item| !matches!(item, ArchivedItem::Task(task, _) if task.id == id));
+    }
+
+    fn retain(&mut self, f: impl FnMut(&ArchivedItem) -> bool) {
+        self.items.retain(f);
+        self.selected = match self.selected {
+            _ if self.items.is_empty() => None,
+            Some(idx) => Some(idx.min(self.items.len() - 1)),
+            None => None,
+        };
+    }
+}
@@ -0,0 +1,44 @@
+use crate::db::{DbError, DbUtils, Session};
+use crate::process::ProcessMsg;
+
+pub enum ConfirmAction {
+    Send(ProcessMsg),
+    // continue the countdown left by the last run
+    Resume(Box<Session>),
+}
+
+// an action waiting for the user to say yes
+pub struct Confirmation {
+    pub title: String,
+    pub detail: String,
+    pub action: ConfirmAction,
+}
+
+impl Confirmation {
+    pub fn new(title: String, action: ProcessMsg) -> Self {
+        Confirmation {
+            title,
+            detail: String::new(),
+            action: ConfirmAction::Send(action),
+        }
+    }
+
+    // fill in how many rows go away with the action
+    pub fn count_affected(&mut self) -> Result<(), DbError> {
+        match self.action {
+            ConfirmAction::Send(ProcessMsg::DeleteInventory(id)) => {
+                let (tasks, tomatos) = DbUtils::inventory_usage(id)?;
+                self.detail = format!(
+                    "{} tasks and {} tomatoes will be deleted for good",
+                    tasks, tomatos
+                );
+            }
+            ConfirmAction::Send(ProcessMsg::DeleteTask(id)) => {
+                let tomatos = DbUtils::task_usage(id)?;
+                self.detail = format!("{} tomatoes will be deleted for good", tomatos);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Local, NaiveDate};
+
+use crate::db::{DbError, DbUtils, Goal};
+
+use super::local_timestamp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Week,
+}
+
+impl Period {
+    pub const ALL: &'static [Period] = &[Period::Day, Period::Week];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Period::Day => "Daily",
+            Period::Week => "Weekly",
+        }
+    }
+
+    pub fn span(&self) -> &'static str {
+        match self {
+            Period::Day => "today",
+            Period::Week => "this week",
+        }
+    }
+
+    fn target(&self, goal: &Goal) -> i64 {
+        match self {
+            Period::Day => goal.daily as i64,
+            Period::Week => goal.weekly as i64,
+        }
+    }
+}
+
+// weeks start on monday
+pub fn week_of(day: NaiveDate) -> NaiveDate {
+    day - chrono::Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+// goals with the tomatos completed toward them on a local date
+pub struct GoalProgress {
+    day: NaiveDate,
+    goals: Vec<Goal>,
+    // inventory id -> (today, this week)
+    counts: HashMap<i32, (i64, i64)>,
+}
+
+impl GoalProgress {
+    pub fn load() -> Result<Self, DbError> {
+        let day = Local::today().naive_local();
+        let counts =
+            DbUtils::completed_per_inventory(local_timestamp(week_of(day)), local_timestamp(day))?;
+        Ok(GoalProgress {
+            day,
+            goals: DbUtils::all_goals()?,
+            counts: counts
+                .into_iter()
+                .map(|c| (c.inventory_id, (c.today, c.week)))
+                .collect(),
+        })
+    }
+
+    // None for all inventories together
+    fn count(&self, inventory_id: Option<i32>, period: Period) -> i64 {
+        let pick = |&(today, week): &(i64, i64)| match period {
+            Period::Day => today,
+            Period::Week => week,
+        };
+        match inventory_id {
+            Some(id) => self.counts.get(&id).map(pick).unwrap_or(0),
+            None => self.counts.values().map(pick).sum(),
+        }
+    }
+
+    // what the count was on this date, as far as an earlier load tells
+    fn count_before(
+        &self,
+        earlier: &GoalProgress,
+        inventory_id: Option<i32>,
+        period: Period,
+    ) -> i64 {
+        let same = match period {
+            Period::Day => earlier.day == self.day,
+            Period::Week => week_of(earlier.day) == week_of(self.day),
+        };
+        if same {
+            earlier.count(inventory_id, period)
+        } else {
+            0
+        }
+    }
+}
+
+/// How far a goal got, e.g. 3 of 8 tomatoes today
+pub struct GoalState {
+    pub period: Period,
+    pub done: i64,
+    pub target: i64,
+}
+
+impl GoalState {
+    pub fn is_reached(&self) -> bool {
+        self.done >= self.target
+    }
+}
+
+// trace: OnKey -> InputModel -> IO process(persist & reload) -> App
+pub struct EditGoal {
+    pub title: String,
+    pub goal: Goal,
+}
+
+pub struct GoalsModel {
+    progress: Option<Box<GoalProgress>>,
+    // the date progress was last asked for
+    day: NaiveDate,
+}
+
+impl GoalsModel {
+    pub fn new() -> Self {
+        GoalsModel {
+            progress: None,
+            day: Local::today().naive_local(),
+        }
+    }
+
+    pub fn goal(&self, inventory_id: Option<i32>) -> Option<&Goal> {
+        self.progress
+            .as_ref()?
+            .goals
+            .iter()
+            .find(|g| g.inventory_id == inventory_id)
+    }
+
+    /// goals set for the inventory, None for all inventories together
+    pub fn states(&self, inventory_id: Option<i32>) -> Vec<GoalState> {
+        let (progress, goal) = match (&self.progress, self.goal(inventory_id)) {
+            (Some(progress), Some(goal)) => (progress, goal),
+            _ => return Vec::new(),
+        };
+        Period::ALL
+            .iter()
+            .filter(|period| period.target(goal) > 0)
+            .map(|&period| GoalState {
+                period,
+                done: progress.count(inventory_id, period),
+                target: period.target(goal),
+            })
+            .collect()
+    }
+
+    // true once a day, the progress is to be loaded again for the new date
+    pub fn day_changed(&mut self) -> bool {
+        let today = Local::today().naive_local();
+        if self.day == today {
+            return false;
+        }
+        self.day = today;
+        true
+    }
+
+    /// goals reached since the previous load are returned, none on the first load
+    pub fn set_progress(&mut self, progress: Box<GoalProgress>) -> Vec<(Option<i32>, GoalState)> {
+        let mut reached = Vec::new();
+        if let Some(earlier) = &self.progress {
+            for goal in &progress.goals {
+                for &period in Period::ALL {
+                    let target = period.target(goal);
+                    let before = progress.count_before(earlier, goal.inventory_id, period);
+                    let done = progress.count(goal.inventory_id, period);
+                    if target > 0 && before < target && done >= target {
+                        reached.push((
+                            goal.inventory_id,
+                            GoalState {
+                                period,
+                                done,
+                                target,
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+        self.progress = Some(progress);
+        reached
+    }
+}
+
+/// "8 40" for 8 tomatoes a day and 40 a week, a missing weekly goal is 0
+pub fn parse_goal_input(input: &str) -> Option<(i32, i32)> {
+    let mut numbers = input.split_whitespace().map(|n| n.parse::<u16>());
+    let daily = numbers.next()?.ok()?;
+    let weekly = match numbers.next() {
+        Some(n) => n.ok()?,
+        None => 0,
+    };
+    if numbers.next().is_some() {
+        return None;
+    }
+    Some((daily as i32, weekly as i32))
+}
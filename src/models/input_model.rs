@@ -5,13 +5,17 @@ use crate::{
     process::{ProcessHandle, ProcessMsg},
 };
 
-use super::{AppHandle, AppMsg, Key};
+use super::{parse_goal_input, AppHandle, AppMsg, EditGoal, EditSetting, Key};
 
 pub enum InputContext {
     NewInventory(Box<NewInventory>),
     NewTask(Box<NewTask>),
     EditTask(Box<EditTask>),
+    // tomatoes, 0 clears the estimate
+    EstimateTask(Box<EditTask>),
     EditInventory(Box<EditInventory>),
+    EditSetting(Box<EditSetting>),
+    EditGoal(Box<EditGoal>),
 }
 
 pub struct InputModel {
@@ -21,7 +25,7 @@ pub struct InputModel {
     input: String,
 }
 
-fn parse_inv_input(input: &str) -> (&str, i32) {
+pub fn parse_inv_input(input: &str) -> (&str, i32) {
     let parts: Vec<_> = input.rsplitn(2, '@').collect();
     if parts.len() == 1 {
         (parts[0], 0)
@@ -76,6 +80,11 @@ impl InputModel {
         self.context = Some(cxt);
     }
 
+    // the current value to edit
+    pub fn set_content(&mut self, content: String) {
+        self.input = content;
+    }
+
     pub fn on_key(&mut self, key: Key) {
         match key {
             Key::Char('\n') => {
@@ -108,6 +117,44 @@ impl InputModel {
                         task.name = Some(input);
                         ProcessMsg::UpdateTask(task)
                     }
+                    InputContext::EstimateTask(mut task) => match input.trim().parse::<u16>() {
+                        Ok(estimate) => {
+                            task.estimate = Some(Some(estimate as i32).filter(|e| *e > 0));
+                            ProcessMsg::UpdateTask(task)
+                        }
+                        Err(_) => {
+                            self.app_hdl
+                                .notify("Estimate must be a number of tomatoes".to_owned());
+                            self.context = Some(InputContext::EstimateTask(task));
+                            return;
+                        }
+                    },
+                    InputContext::EditSetting(mut setting) => match input.trim().parse() {
+                        Ok(value) => {
+                            setting.field.set(&mut setting.base, value);
+                            ProcessMsg::UpdateConfig(Box::new(setting.base))
+                        }
+                        Err(_) => {
+                            self.app_hdl
+                                .notify(format!("{} must be a number", setting.field.title()));
+                            self.context = Some(InputContext::EditSetting(setting));
+                            return;
+                        }
+                    },
+                    InputContext::EditGoal(mut edit) => match parse_goal_input(&input) {
+                        Some((daily, weekly)) => {
+                            edit.goal.daily = daily;
+                            edit.goal.weekly = weekly;
+                            ProcessMsg::SetGoal(Box::new(edit.goal))
+                        }
+                        None => {
+                            self.app_hdl.notify(
+                                "Goals are tomatoes per day and per week, like 8 40".to_owned(),
+                            );
+                            self.context = Some(InputContext::EditGoal(edit));
+                            return;
+                        }
+                    },
                 };
                 self.app_hdl.send(AppMsg::InputEnd);
                 self.proc_hdl.send(msg);
@@ -118,6 +165,7 @@ impl InputModel {
             }
             Key::Esc => {
                 self.context = None;
+                self.input.clear();
                 self.app_hdl.send(AppMsg::InputEnd);
             }
             _ => {}
@@ -1,31 +1,96 @@
-use crate::db::{DbUtils, EditInventory, EditTask, Inventory, Task};
+use crate::db::{DbError, DbUtils, EditInventory, EditTask, Inventory, Task};
 use std::collections::HashMap;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskFilter {
+    Open,
+    Completed,
+    All,
+}
+
+impl TaskFilter {
+    pub fn title(&self) -> &'static str {
+        match self {
+            TaskFilter::Open => "open",
+            TaskFilter::Completed => "completed",
+            TaskFilter::All => "all",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            TaskFilter::Open => TaskFilter::Completed,
+            TaskFilter::Completed => TaskFilter::All,
+            TaskFilter::All => TaskFilter::Open,
+        }
+    }
+
+    fn shows(&self, task: &Task) -> bool {
+        match self {
+            TaskFilter::Open => task.completed_at.is_none(),
+            TaskFilter::Completed => task.completed_at.is_some(),
+            TaskFilter::All => true,
+        }
+    }
+}
+
+/// A `/` search of task names, see `fuzzy_match`
+pub struct TaskSearch {
+    pub query: String,
+    // Up/Down go through the matches of every inventory
+    pub all_inventories: bool,
+}
+
+/// char indices of the text matching the query chars in order, case and spaces are ignored
+pub fn fuzzy_match(query: &str, text: &str) -> Option<Vec<usize>> {
+    let mut chars = text.chars().enumerate();
+    let mut positions = Vec::new();
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let q = q.to_lowercase();
+        let (idx, _) = chars
+            .by_ref()
+            .find(|(_, c)| c.to_lowercase().eq(q.clone()))?;
+        positions.push(idx);
+    }
+    Some(positions)
+}
+
 pub struct InventoryModel {
     pub inventory_selected: Option<usize>,
+    // indices in tasks_list, hidden tasks are never selected
     pub task_selected: Vec<Option<usize>>,
     pub inventory_list: Vec<Inventory>,
     pub tasks_list: Vec<Vec<Task>>,
+    pub task_filter: TaskFilter,
+    pub search: Option<TaskSearch>,
     task2inv: HashMap<i32, usize>, // task_id map to inventory index
 }
 
 impl InventoryModel {
-    pub fn new() -> Self {
-        let inventory_list = DbUtils::all_inventory();
-        let tasks_list = DbUtils::all_task_groupby(&inventory_list);
+    pub fn new() -> Result<Self, DbError> {
+        let inventory_list = DbUtils::all_inventory()?;
+        let tasks_list = DbUtils::all_task_groupby(&inventory_list)?;
         assert_eq!(inventory_list.len(), tasks_list.len());
-        let task2inv = tasks_list
-            .iter()
-            .enumerate()
-            .flat_map(|(idx, tasks)| tasks.iter().map(move |task| (task.id, idx)))
-            .collect();
-        InventoryModel {
+        let mut model = InventoryModel {
             task_selected: vec![None; tasks_list.len()],
             inventory_selected: None,
             inventory_list,
             tasks_list,
-            task2inv,
-        }
+            task_filter: TaskFilter::Open,
+            search: None,
+            task2inv: HashMap::new(),
+        };
+        model.rebuild_task2inv();
+        Ok(model)
+    }
+
+    fn rebuild_task2inv(&mut self) {
+        self.task2inv = self
+            .tasks_list
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, tasks)| tasks.iter().map(move |task| (task.id, idx)))
+            .collect();
     }
 
     fn inventory_idx_by_id(&self, id: i32) -> Option<usize> {
@@ -50,9 +115,30 @@ impl InventoryModel {
     }
 
     pub fn delete_task(&mut self, task_id: i32) {
-        if let Some(idx) = self.task2inv.remove(&task_id) {
-            self.tasks_list[idx].retain(|t| t.id != task_id);
-            self.next_task();
+        let idx = match self.task2inv.remove(&task_id) {
+            Some(idx) => idx,
+            None => return,
+        };
+        let pos = match self.tasks_list[idx].iter().position(|t| t.id == task_id) {
+            Some(pos) => pos,
+            None => return,
+        };
+        self.tasks_list[idx].remove(pos);
+        // the inventory may not be the selected one, its selection is fixed all the same:
+        // later tasks shift down, the one after a removed selection takes its place
+        if let Some(selected) = self.task_selected[idx] {
+            let selected = if selected > pos {
+                selected - 1
+            } else {
+                selected
+            };
+            let len = self.tasks_list[idx].len();
+            self.task_selected[idx] = if selected < len {
+                Some(selected)
+            } else {
+                len.checked_sub(1)
+            };
+            self.reselect_task(idx);
         }
     }
 
@@ -60,13 +146,21 @@ impl InventoryModel {
         if let Some(idx) = self.inventory_idx_by_id(inventory_id) {
             self.inventory_list.remove(idx);
             self.task_selected.remove(idx);
-            for task in self.tasks_list.remove(idx).iter() {
-                self.task2inv.remove(&task.id);
-            }
+            self.tasks_list.remove(idx);
+            // inventories after idx are shifted
+            self.rebuild_task2inv();
             self.next_inventory();
         }
     }
 
+    // a restored inventory comes back with its tasks
+    pub fn push_restored_inventory(&mut self, inv: Inventory, tasks: Vec<Task>) {
+        self.push_new_inventory(inv);
+        for task in tasks {
+            self.push_new_task(task);
+        }
+    }
+
     pub fn edit_inventory(&mut self, inv: Box<EditInventory>) {
         let _ = self
             .inventory_list
@@ -89,17 +183,163 @@ impl InventoryModel {
                 .iter_mut()
                 .find(|t| t.id == task.id)
                 .and_then(|t| {
-                    t.name = task.name.unwrap();
+                    if let Some(name) = task.name {
+                        t.name = name;
+                    }
+                    if let Some(estimate) = task.estimate {
+                        t.estimate = estimate;
+                    }
+                    if let Some(completed_at) = task.completed_at {
+                        t.completed_at = completed_at;
+                    }
+                    if let Some(notes) = task.notes {
+                        t.notes = notes;
+                    }
                     Option::<()>::None
                 });
+            self.reselect_task(*idx);
+        }
+    }
+
+    /// indices in tasks_list of the tasks shown by the filter and the search
+    pub fn visible_tasks(&self, iidx: usize) -> Vec<usize> {
+        self.tasks_list[iidx]
+            .iter()
+            .enumerate()
+            .filter(|(_, task)| self.task_filter.shows(task))
+            .filter(|(_, task)| self.matched_chars(&task.name).is_some())
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// chars of the name matching the search, Some(empty) without a search
+    pub fn matched_chars(&self, name: &str) -> Option<Vec<usize>> {
+        match &self.search {
+            Some(search) => fuzzy_match(&search.query, name),
+            None => Some(Vec::new()),
+        }
+    }
+
+    pub fn searches_all(&self) -> bool {
+        matches!(&self.search, Some(search) if search.all_inventories)
+    }
+
+    // an earlier query is kept to be refined
+    pub fn start_search(&mut self, all_inventories: bool) {
+        let query = self.search.take().map(|s| s.query).unwrap_or_default();
+        self.search = Some(TaskSearch {
+            query,
+            all_inventories,
+        });
+        self.refilter();
+    }
+
+    pub fn push_search(&mut self, c: char) {
+        if let Some(search) = self.search.as_mut() {
+            search.query.push(c);
+            self.refilter();
+        }
+    }
+
+    pub fn pop_search(&mut self) {
+        if let Some(search) = self.search.as_mut() {
+            search.query.pop();
+            self.refilter();
+        }
+    }
+
+    pub fn toggle_search_scope(&mut self) {
+        if let Some(search) = self.search.as_mut() {
+            search.all_inventories = !search.all_inventories;
+            self.refilter();
+        }
+    }
+
+    // the selection stays on the task found
+    pub fn clear_search(&mut self) {
+        self.search = None;
+        self.refilter();
+    }
+
+    // every inventory selects a shown task if any, searching all inventories
+    // moves to the first one with matches when the selected one has none
+    fn refilter(&mut self) {
+        for iidx in 0..self.tasks_list.len() {
+            self.reselect_task(iidx);
+            if self.task_selected[iidx].is_none() {
+                self.task_selected[iidx] = self.visible_tasks(iidx).first().copied();
+            }
+        }
+        if self.searches_all() && self.get_task_location().is_none() {
+            if let Some(iidx) =
+                (0..self.tasks_list.len()).find(|i| self.task_selected[*i].is_some())
+            {
+                self.inventory_selected = Some(iidx);
+            }
+        }
+    }
+
+    // (inventory, task) of the shown tasks of every inventory
+    fn all_matches(&self) -> Vec<(usize, usize)> {
+        (0..self.tasks_list.len())
+            .flat_map(|iidx| {
+                self.visible_tasks(iidx)
+                    .into_iter()
+                    .map(move |tidx| (iidx, tidx))
+            })
+            .collect()
+    }
+
+    fn step_match(&mut self, forward: bool) {
+        let matches = self.all_matches();
+        let loc = self.get_task_location();
+        let pos = loc.and_then(|loc| matches.iter().position(|m| *m == loc));
+        let pos = if forward {
+            self.next(pos, &matches)
+        } else {
+            self.previous(pos, &matches)
+        };
+        if let Some((iidx, tidx)) = pos.map(|pos| matches[pos]) {
+            self.inventory_selected = Some(iidx);
+            self.task_selected[iidx] = Some(tidx);
         }
     }
 
+    pub fn next_filter(&mut self) {
+        self.task_filter = self.task_filter.next();
+        for iidx in 0..self.tasks_list.len() {
+            self.reselect_task(iidx);
+        }
+    }
+
+    // a hidden selection moves to the next shown task, or the last one
+    fn reselect_task(&mut self, iidx: usize) {
+        let selected = match self.task_selected[iidx] {
+            Some(tidx) => tidx,
+            None => return,
+        };
+        let visible = self.visible_tasks(iidx);
+        if visible.contains(&selected) {
+            return;
+        }
+        self.task_selected[iidx] = visible
+            .iter()
+            .find(|tidx| **tidx > selected)
+            .or_else(|| visible.last())
+            .copied();
+    }
+
     pub fn get_task_location(&self) -> Option<(usize, usize)> {
         self.inventory_selected
             .and_then(|iidx| self.task_selected[iidx].map(|tidx| (iidx, tidx)))
     }
 
+    pub fn task_location(&self, task_id: i32) -> Option<(usize, usize)> {
+        let iidx = *self.task2inv.get(&task_id)?;
+        let tidx = self.tasks_list[iidx].iter().position(|t| t.id == task_id)?;
+        Some((iidx, tidx))
+    }
+
     fn next<T>(&self, selected: Option<usize>, list: &[T]) -> Option<usize> {
         if list.is_empty() {
             return None;
@@ -124,9 +364,15 @@ impl InventoryModel {
         self.inventory_selected = self.next(self.inventory_selected, &self.inventory_list);
     }
 
+    // moves in the filtered view
     pub fn next_task(&mut self) {
+        if self.searches_all() {
+            return self.step_match(true);
+        }
         if let Some(idx) = self.inventory_selected {
-            self.task_selected[idx] = self.next(self.task_selected[idx], &self.tasks_list[idx]);
+            let visible = self.visible_tasks(idx);
+            let pos = self.task_selected[idx].and_then(|t| visible.iter().position(|v| *v == t));
+            self.task_selected[idx] = self.next(pos, &visible).map(|pos| visible[pos]);
         }
     }
 
@@ -135,8 +381,221 @@ impl InventoryModel {
     }
 
     pub fn previous_task(&mut self) {
+        if self.searches_all() {
+            return self.step_match(false);
+        }
         if let Some(idx) = self.inventory_selected {
-            self.task_selected[idx] = self.previous(self.task_selected[idx], &self.tasks_list[idx]);
+            let visible = self.visible_tasks(idx);
+            let pos = self.task_selected[idx].and_then(|t| visible.iter().position(|v| *v == t));
+            self.task_selected[idx] = self.previous(pos, &visible).map(|pos| visible[pos]);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (task name, completed) of each inventory, nothing selected
+    fn model(inventories: &[&[(&str, bool)]]) -> InventoryModel {
+        let mut id = 0;
+        let tasks_list: Vec<Vec<Task>> = inventories
+            .iter()
+            .enumerate()
+            .map(|(iidx, tasks)| {
+                tasks
+                    .iter()
+                    .map(|(name, completed)| {
+                        id += 1;
+                        Task {
+                            id,
+                            inventory_id: iidx as i32,
+                            name: name.to_string(),
+                            completed_at: if *completed { Some(0) } else { None },
+                            ..Default::default()
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut model = InventoryModel {
+            inventory_selected: None,
+            task_selected: vec![None; tasks_list.len()],
+            inventory_list: (0..tasks_list.len() as i32)
+                .map(|id| Inventory {
+                    id,
+                    ..Default::default()
+                })
+                .collect(),
+            tasks_list,
+            task_filter: TaskFilter::Open,
+            search: None,
+            task2inv: HashMap::new(),
+        };
+        model.rebuild_task2inv();
+        model
+    }
+
+    #[test]
+    fn filter_shows_tasks_by_state() {
+        let open = Task::default();
+        let completed = Task {
+            completed_at: Some(0),
+            ..Default::default()
+        };
+        assert!(TaskFilter::Open.shows(&open));
+        assert!(!TaskFilter::Open.shows(&completed));
+        assert!(!TaskFilter::Completed.shows(&open));
+        assert!(TaskFilter::Completed.shows(&completed));
+        assert!(TaskFilter::All.shows(&open));
+        assert!(TaskFilter::All.shows(&completed));
+    }
+
+    #[test]
+    fn hidden_selection_moves_to_next_shown() {
+        let mut model = model(&[&[("a", false), ("b", true), ("c", false), ("d", true)]]);
+        model.task_filter = TaskFilter::All;
+        model.task_selected[0] = Some(1);
+        model.task_filter = TaskFilter::Open;
+        model.reselect_task(0);
+        assert_eq!(model.task_selected[0], Some(2));
+
+        // a shown selection stays
+        model.reselect_task(0);
+        assert_eq!(model.task_selected[0], Some(2));
+    }
+
+    #[test]
+    fn hidden_selection_falls_back_to_last_shown() {
+        let mut model = model(&[&[("a", false), ("b", false), ("c", true)]]);
+        model.task_selected[0] = Some(2);
+        model.reselect_task(0);
+        assert_eq!(model.task_selected[0], Some(1));
+
+        model.task_filter = TaskFilter::Completed;
+        model.task_selected[0] = Some(0);
+        model.reselect_task(0);
+        assert_eq!(model.task_selected[0], Some(2));
+
+        // nothing shown, nothing selected
+        model.tasks_list[0][2].completed_at = None;
+        model.reselect_task(0);
+        assert_eq!(model.task_selected[0], None);
+    }
+
+    #[test]
+    fn delete_keeps_selection_in_bounds() {
+        let mut model = model(&[&[("a", false), ("b", false)], &[("c", false)]]);
+        model.task_selected[0] = Some(1);
+        model.inventory_selected = Some(1);
+        model.task_selected[1] = Some(0);
+
+        // the last task of an inventory not selected
+        model.delete_task(2);
+        assert_eq!(model.task_selected[0], Some(0));
+        assert_eq!(model.get_task_location(), Some((1, 0)));
+        model.inventory_selected = Some(0);
+        assert_eq!(model.get_task_location(), Some((0, 0)));
+
+        model.delete_task(1);
+        assert_eq!(model.task_selected[0], None);
+    }
+
+    #[test]
+    fn delete_before_selection_shifts_it() {
+        // ids are 1 to 4
+        let mut model = model(&[&[("a", false), ("b", false), ("c", false), ("d", false)]]);
+        model.task_selected[0] = Some(2);
+        model.delete_task(2);
+        assert_eq!(model.task_selected[0], Some(1));
+        assert_eq!(model.tasks_list[0][1].name, "c");
+
+        // the next task takes the place of a removed selection
+        model.delete_task(3);
+        assert_eq!(model.task_selected[0], Some(1));
+        assert_eq!(model.tasks_list[0][1].name, "d");
+
+        // and the previous one when it was the last
+        model.delete_task(4);
+        assert_eq!(model.task_selected[0], Some(0));
+    }
+
+    #[test]
+    fn fuzzy_match_in_order_ignoring_case() {
+        assert_eq!(fuzzy_match("RT", "report"), Some(vec![0, 5]));
+        assert_eq!(fuzzy_match("tr", "report"), None);
+        assert_eq!(fuzzy_match("", "report"), Some(vec![]));
+    }
+
+    #[test]
+    fn fuzzy_match_ignores_whitespace() {
+        assert_eq!(fuzzy_match(" r  t ", "report"), fuzzy_match("rt", "report"));
+        assert_eq!(fuzzy_match("e m", "read mail"), Some(vec![1, 5]));
+    }
+
+    #[test]
+    fn fuzzy_match_positions_are_chars() {
+        assert_eq!(fuzzy_match("指定", "未指定"), Some(vec![1, 2]));
+        assert_eq!(fuzzy_match("定指", "未指定"), None);
+        assert_eq!(fuzzy_match("d", "未指定 doc"), Some(vec![4]));
+    }
+
+    #[test]
+    fn search_moves_selection_off_hidden_task() {
+        let mut model = model(&[&[("report", false), ("email", false), ("read", false)]]);
+        model.inventory_selected = Some(0);
+        model.task_selected[0] = Some(1);
+
+        model.start_search(false);
+        assert_eq!(model.get_task_location(), Some((0, 1)));
+        model.push_search('r');
+        assert_eq!(model.visible_tasks(0), vec![0, 2]);
+        assert_eq!(model.get_task_location(), Some((0, 2)));
+
+        // the task found stays selected without the search
+        model.clear_search();
+        assert_eq!(model.visible_tasks(0), vec![0, 1, 2]);
+        assert_eq!(model.get_task_location(), Some((0, 2)));
+    }
+
+    #[test]
+    fn search_selects_first_match() {
+        let mut model = model(&[&[("report", false), ("email", false)]]);
+        model.inventory_selected = Some(0);
+        model.start_search(false);
+        model.push_search('m');
+        assert_eq!(model.get_task_location(), Some((0, 1)));
+        model.push_search('x');
+        assert_eq!(model.get_task_location(), None);
+    }
+
+    #[test]
+    fn search_steps_across_inventories() {
+        let mut model = model(&[
+            &[("report", false), ("email", false)],
+            &[("mail", false)],
+            &[("rust", false), ("read", false)],
+        ]);
+        model.inventory_selected = Some(1);
+        model.start_search(true);
+        model.push_search('r');
+        // no match in the selected inventory, the first one with matches is picked
+        assert_eq!(model.get_task_location(), Some((0, 0)));
+
+        model.next_task();
+        assert_eq!(model.get_task_location(), Some((2, 0)));
+        model.next_task();
+        assert_eq!(model.get_task_location(), Some((2, 1)));
+        model.next_task();
+        assert_eq!(model.get_task_location(), Some((0, 0)));
+        model.previous_task();
+        assert_eq!(model.get_task_location(), Some((2, 1)));
+
+        // one inventory at a time again
+        model.toggle_search_scope();
+        model.next_task();
+        assert_eq!(model.get_task_location(), Some((2, 0)));
+        model.next_task();
+        assert_eq!(model.get_task_location(), Some((2, 1)));
+    }
+}
@@ -1,22 +1,52 @@
+mod archive_model;
+mod confirm_model;
+mod goal_model;
 mod input_model;
 mod inventory_model;
 mod navitab_model;
+mod notes_model;
+mod settings_model;
+mod statistic_model;
+mod switcher_model;
 mod tomato_model;
+mod undo_model;
 
 use crate::{
-    db::{EditInventory, EditTask, Inventory, NewInventory, NewTask, Task, Tomato},
+    config::Config,
+    db::{
+        DbError, DbUtils, EditInventory, EditTask, Goal, Inventory, NewInventory, NewTask, Outcome,
+        Session, Task, Tomato, TomatoDetail,
+    },
     events::Key,
+    hooks::{Hook, HooksConfig},
     process::{ProcessHandle, ProcessMsg},
 };
 
 use inventory_model::InventoryModel;
 
-pub use input_model::{InputContext, InputModel};
+pub use archive_model::{ArchiveModel, ArchivedItem};
+pub use confirm_model::{ConfirmAction, Confirmation};
+pub use goal_model::{parse_goal_input, week_of, EditGoal, GoalProgress, GoalState, GoalsModel};
+pub use input_model::{parse_inv_input, InputContext, InputModel};
 pub use navitab_model::{NavitabModel, TabType};
-pub use tomato_model::TomatoModel;
+pub use notes_model::{NotesDraft, NotesModel};
+pub use settings_model::{EditSetting, SettingField, SettingsModel};
+pub use statistic_model::{
+    format_duration, local_timestamp, tomatos_of_day, StatRange, Statistics, StatisticsModel,
+};
+pub use switcher_model::SwitcherModel;
+pub use tomato_model::{describe_session, TomatoConfig, TomatoModel};
+pub use undo_model::{Operation, UndoModel};
 
+use chrono::NaiveDate;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
+// id of the default inventory and task, tomatos without a task belong to them
+const DEFAULT_ID: i32 = 0;
+// a running countdown is saved this often, in case the app is killed
+const SESSION_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
 pub enum AppMsg {
     Notify(String),
     // trace: TomatoModel lanuch -> App(append id info) -> IO process
@@ -29,11 +59,39 @@ pub enum AppMsg {
     DeleteInventory(i32),
     // trace: OnKey -> IO process -> App
     DeleteTask(i32),
-    // trace: InputModel -> App
+    // trace: InputModel | NotesModel -> App
     InputEnd,
     _Callback(fn(&mut App, Vec<u8>)),
     EditInventory(Box<EditInventory>),
     EditTask(Box<EditTask>),
+    // trace: InputModel -> IO process(validate & persist) -> App
+    UpdateConfig(Box<Config>),
+    // trace: OnKey -> IO process(aggregate) -> App
+    Statistics(Box<Statistics>),
+    // trace: OnKey -> IO process -> App
+    DayTomatos(NaiveDate, Vec<TomatoDetail>),
+    // trace: OnKey -> IO process -> App
+    Archived(Vec<ArchivedItem>),
+    // trace: OnKey -> IO process(unarchive) -> App
+    RestoreInventory(Box<Inventory>, Vec<Task>),
+    // trace: OnKey -> IO process(unarchive) -> App
+    RestoreTask(Box<Task>),
+    // trace: OnKey -> IO process(applied) -> App
+    Done(Box<Operation>),
+    // trace: OnKey(u) -> IO process(reverted) -> App
+    Undone(Box<Operation>),
+    // trace: OnKey(^r) -> IO process(reapplied) -> App
+    Redone(Box<Operation>),
+    // trace: OnKey(u) -> IO process(revert failed) -> App
+    UndoFailed(Box<Operation>),
+    // trace: OnKey(^r) -> IO process(reapply failed) -> App
+    RedoFailed(Box<Operation>),
+    // trace: OnKey -> IO process(count affected rows) -> App
+    Confirm(Box<Confirmation>),
+    // trace: TomatoModel -> App(append task names) -> IO process
+    Hook(Box<Hook>),
+    // trace: TomatoClose | OnKey -> IO process(count tomatos) -> App
+    Goals(Box<GoalProgress>),
 }
 
 #[derive(Clone)]
@@ -56,24 +114,51 @@ impl AppHandle {
 }
 
 #[derive(Default)]
-pub struct AppBuilder {}
+pub struct AppBuilder {
+    config: Config,
+}
 
 impl AppBuilder {
-    pub fn build(self) -> App {
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn build(self) -> Result<App, DbError> {
         let (sender, receiver) = unbounded_channel();
         let app_handle = AppHandle { sender };
         let process_handle = ProcessHandle::new(app_handle.clone());
 
-        App {
+        let mut app = App {
             receiver,
             process_handle: process_handle.clone(),
             active_blocks: Vec::new(),
-            inventory: InventoryModel::new(),
-            tomato: TomatoModel::new(app_handle.clone(), process_handle.clone()),
+            inventory: InventoryModel::new()?,
+            tomato: TomatoModel::new(
+                app_handle.clone(),
+                process_handle.clone(),
+                self.config.timer,
+                &self.config.notify,
+            ),
             tabs: NavitabModel::new(),
+            settings: SettingsModel::new(),
+            statistics: StatisticsModel::new(),
+            archive: ArchiveModel::new(),
+            goals: GoalsModel::new(),
+            switcher: SwitcherModel::new()?,
+            undo: UndoModel::default(),
+            confirm: None,
+            hooks: self.config.hooks,
             notify: None,
+            notes: NotesModel::new(app_handle.clone(), process_handle.clone()),
             input: InputModel::new(app_handle, process_handle),
+            session_saved: Instant::now(),
+        };
+        if let Some(session) = DbUtils::load_session()? {
+            app.offer_resume(session);
         }
+        app.process_handle.send(ProcessMsg::LoadGoals);
+        Ok(app)
     }
 }
 
@@ -84,8 +169,18 @@ pub struct App {
     pub inventory: InventoryModel,
     pub tomato: TomatoModel,
     pub tabs: NavitabModel,
+    pub settings: SettingsModel,
+    pub statistics: StatisticsModel,
+    pub archive: ArchiveModel,
+    pub goals: GoalsModel,
+    pub switcher: SwitcherModel,
+    undo: UndoModel,
+    pub confirm: Option<Box<Confirmation>>,
+    hooks: HooksConfig,
     pub notify: Option<String>,
     pub input: InputModel,
+    pub notes: NotesModel,
+    session_saved: Instant,
 }
 
 impl App {
@@ -101,7 +196,10 @@ impl App {
 
                     let task = &mut self.inventory.tasks_list[iidx][tidx];
                     t.task_id = task.id;
-                    task.spent_minutes += t.end_time - t.start_time;
+                    task.spent_seconds += t.focused_seconds();
+                    if Outcome::from(t.outcome) == Outcome::Completed {
+                        task.spent_tomatos += 1;
+                    }
                 }
 
                 self.process_handle.close_tomato(t);
@@ -112,23 +210,288 @@ impl App {
             NewTask(task) => {
                 self.inventory.push_new_task(*task);
             }
-            DeleteInventory(id) => self.inventory.delete_inventory(id),
-            DeleteTask(id) => self.inventory.delete_task(id),
+            DeleteInventory(id) => {
+                self.keep_bound_task(|inventory| inventory.delete_inventory(id));
+                self.archive.remove_inventory(id);
+                // goals of a purged inventory are gone
+                self.process_handle.send(ProcessMsg::LoadGoals);
+            }
+            DeleteTask(id) => {
+                self.keep_bound_task(|inventory| inventory.delete_task(id));
+                self.archive.remove_task(id);
+            }
             InputEnd => self.pop_block(),
             EditInventory(inv) => self.inventory.edit_inventory(inv),
-            EditTask(task) => self.inventory.edit_task(task),
+            EditTask(task) => {
+                let bound = self
+                    .tomato
+                    .where_idx()
+                    .map(|(iidx, tidx)| self.inventory.tasks_list[iidx][tidx].id);
+                let completed = matches!(task.completed_at, Some(Some(_)));
+                let id = task.id;
+                self.inventory.edit_task(task);
+                if completed && bound == Some(id) {
+                    self.pick_next_task();
+                }
+            }
+            UpdateConfig(config) => {
+                self.tomato.set_config(config.timer);
+                self.tomato.set_notify_config(&config.notify);
+                self.hooks = config.hooks;
+            }
+            Statistics(stat) => self.statistics.set_data(stat),
+            DayTomatos(day, tomatos) => self.statistics.set_day_tomatos(day, tomatos),
+            Archived(items) => self.archive.set_items(items),
+            RestoreInventory(inv, tasks) => {
+                self.archive.remove_inventory(inv.id);
+                self.inventory.push_restored_inventory(*inv, tasks);
+                self.process_handle.send(ProcessMsg::LoadGoals);
+            }
+            RestoreTask(task) => {
+                self.archive.remove_task(task.id);
+                self.inventory.push_new_task(*task);
+            }
+            Done(op) => self.undo.push(*op),
+            Undone(op) => {
+                self.notify = Some(format!("Undid {}", op.describe()));
+                self.undo.push_redo(*op);
+            }
+            Redone(op) => {
+                self.notify = Some(format!("Redid {}", op.describe()));
+                self.undo.push_undo(*op);
+            }
+            UndoFailed(op) => self.undo.push_undo(*op),
+            RedoFailed(op) => self.undo.push_redo(*op),
+            Hook(mut hook) => {
+                if let Some((iidx, tidx)) = self.tomato.where_idx() {
+                    hook.inventory = self.inventory.inventory_list[iidx].name.clone();
+                    hook.task = self.inventory.tasks_list[iidx][tidx].name.clone();
+                }
+                if let Some(cmd) = hook.command(&self.hooks) {
+                    self.process_handle
+                        .send(ProcessMsg::RunCommand(hook.event.label(), Box::new(cmd)));
+                }
+            }
+            Confirm(confirm) => {
+                self.confirm = Some(confirm);
+                self.push_block(ActiveBlock::Confirm);
+            }
+            Goals(progress) => {
+                let reached = self.goals.set_progress(progress);
+                if !reached.is_empty() {
+                    let msg = reached
+                        .iter()
+                        .map(|(id, state)| self.describe_reached(*id, state))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    self.tomato.notify(&msg);
+                    self.notify = Some(msg);
+                }
+            }
             _Callback(f) => f(self, Vec::new()),
         }
+        // tasks may have come, gone or moved under the switcher
+        if self.is_block_open(ActiveBlock::Switcher) {
+            self.switcher.refresh(&self.inventory.tasks_list);
+        }
+    }
+
+    pub fn undo(&mut self) {
+        match self.undo.pop_undo() {
+            Some(op) => self.process_handle.send(ProcessMsg::Undo(Box::new(op))),
+            None => self.notify = Some("Nothing to undo".to_owned()),
+        }
+    }
+
+    pub fn redo(&mut self) {
+        match self.undo.pop_redo() {
+            Some(op) => self.process_handle.send(ProcessMsg::Redo(Box::new(op))),
+            None => self.notify = Some("Nothing to redo".to_owned()),
+        }
+    }
+
+    // removals shift indices, so the task bound to the countdown is located again by id
+    fn keep_bound_task(&mut self, change: impl FnOnce(&mut InventoryModel)) {
+        let bound = self
+            .tomato
+            .where_idx()
+            .map(|(iidx, tidx)| self.inventory.tasks_list[iidx][tidx].id);
+        change(&mut self.inventory);
+        match bound.and_then(|id| self.inventory.task_location(id)) {
+            Some(loc) => self.tomato.set_where_idx(loc),
+            None => self.tomato.clear_where_idx(),
+        }
+    }
+
+    // a resume not answered yet is kept as it is
+    pub fn session(&self) -> Option<Session> {
+        if let Some(ConfirmAction::Resume(session)) = self.confirm.as_ref().map(|c| &c.action) {
+            return Some((**session).clone());
+        }
+        let task_id = self
+            .tomato
+            .where_idx()
+            .map(|(iidx, tidx)| self.inventory.tasks_list[iidx][tidx].id);
+        self.tomato.session(task_id)
+    }
+
+    fn save_session(&mut self) {
+        self.session_saved = Instant::now();
+        let session = self.session().map(Box::new);
+        self.process_handle.send(ProcessMsg::SaveSession(session));
+    }
+
+    // a bound task alone is brought back without asking
+    fn offer_resume(&mut self, session: Session) {
+        let task = session
+            .task_id
+            .and_then(|id| self.inventory.task_location(id));
+        if session.started_at.is_none() && session.phase_idx == 0 {
+            if let Some(loc) = task {
+                self.tomato.set_where_idx(loc);
+            }
+            return;
+        }
+        let mut detail = describe_session(&session);
+        if let Some((iidx, tidx)) = task {
+            detail = format!(
+                "{}, task {:?}",
+                detail, self.inventory.tasks_list[iidx][tidx].name
+            );
+        }
+        self.confirm = Some(Box::new(Confirmation {
+            title: "Resume the last countdown?".to_owned(),
+            detail,
+            action: ConfirmAction::Resume(Box::new(session)),
+        }));
+        self.push_block(ActiveBlock::Confirm);
+    }
+
+    fn resume(&mut self, session: &Session) {
+        self.tomato.resume(session);
+        match session
+            .task_id
+            .and_then(|id| self.inventory.task_location(id))
+        {
+            Some(loc) => self.tomato.set_where_idx(loc),
+            None => self.tomato.clear_where_idx(),
+        }
+        self.tabs.select = TabType::Tomato as usize;
+        self.save_session();
+    }
+
+    // marked done, or open again if done
+    fn toggle_task(&self, task: &Task) {
+        let completed_at = match task.completed_at {
+            Some(_) => None,
+            None => Some(chrono::Local::now().timestamp()),
+        };
+        let edit = EditTask {
+            id: task.id,
+            completed_at: Some(completed_at),
+            ..Default::default()
+        };
+        self.process_handle
+            .send(ProcessMsg::UpdateTask(Box::new(edit)));
+    }
+
+    // the timer goes on with the task, shown on the tomato tab
+    fn bind_task(&mut self, loc: (usize, usize)) {
+        let (iidx, tidx) = loc;
+        self.switcher
+            .touch(self.inventory.tasks_list[iidx][tidx].id);
+        self.tomato.set_where_idx(loc);
+        self.save_session();
+        self.reset_block();
+        self.tabs.select = TabType::Tomato as usize;
+    }
+
+    // the bound task is done, the next one is picked in its task list
+    fn pick_next_task(&mut self) {
+        let (iidx, tidx) = match self.tomato.where_idx() {
+            Some(loc) => loc,
+            None => return,
+        };
+        self.inventory.inventory_selected = Some(iidx);
+        self.inventory.task_selected[iidx] = Some(tidx);
+        self.inventory.next_task();
+        self.tabs.select = TabType::Inventory as usize;
+        self.reset_block();
+        self.push_block(ActiveBlock::InventoryList);
+        self.push_block(ActiveBlock::TaskList);
+        self.notify = Some("Task done, pick the next one with Enter".to_owned());
+    }
+
+    fn describe_reached(&self, inventory_id: Option<i32>, state: &GoalState) -> String {
+        let of = inventory_id
+            .and_then(|id| self.inventory.inventory_list.iter().find(|i| i.id == id))
+            .map(|inv| format!(" of {}", inv.name))
+            .unwrap_or_default();
+        format!(
+            "{} goal{} reached: {} tomatoes",
+            state.period.title(),
+            of,
+            state.done
+        )
+    }
+
+    // None for the goal of all inventories together
+    fn edit_goal(&mut self, inventory: Option<&Inventory>) {
+        let title = match inventory {
+            Some(inv) => format!("Goal of {}: tomatoes per day and per week", inv.name),
+            None => "Goal: tomatoes per day and per week".to_owned(),
+        };
+        let inventory_id = inventory.map(|inv| inv.id);
+        // the goal set so far is edited
+        let goal = match self.goals.goal(inventory_id) {
+            Some(goal) => {
+                self.input
+                    .set_content(format!("{} {}", goal.daily, goal.weekly));
+                goal.clone()
+            }
+            None => Goal {
+                inventory_id,
+                ..Default::default()
+            },
+        };
+        self.push_block(ActiveBlock::Input);
+        self.input
+            .set_context(InputContext::EditGoal(Box::new(EditGoal { title, goal })));
+    }
+
+    pub fn open_archive(&mut self) {
+        self.archive.set_items(Vec::new());
+        self.push_block(ActiveBlock::Archived);
+        self.process_handle.send(ProcessMsg::LoadArchived);
+    }
+
+    pub fn refresh_statistics(&self) {
+        self.process_handle
+            .send(ProcessMsg::LoadStatistics(self.statistics.range));
     }
 
     pub fn on_tick(&mut self) {
+        if self.goals.day_changed() {
+            self.process_handle.send(ProcessMsg::LoadGoals);
+        }
+        let phase = self.tomato.phase_idx();
         self.tomato.on_tick();
+        // a session saved before the switch would close the ended focus again
+        if phase != self.tomato.phase_idx() || self.session_saved.elapsed() >= SESSION_SAVE_INTERVAL
+        {
+            self.save_session();
+        }
     }
 
     pub fn active_block(&self) -> ActiveBlock {
         *self.active_blocks.last().unwrap_or(&ActiveBlock::Navitab)
     }
 
+    // the block is active or below popups on the stack
+    pub fn is_block_open(&self, block: ActiveBlock) -> bool {
+        self.active_blocks.contains(&block)
+    }
+
     pub fn push_block(&mut self, block: ActiveBlock) {
         self.active_blocks.push(block);
     }
@@ -142,7 +505,10 @@ impl App {
     }
 
     pub fn is_q_quit_enable(&self) -> bool {
-        self.active_block() != ActiveBlock::Input
+        !matches!(
+            self.active_block(),
+            ActiveBlock::Input | ActiveBlock::Notes | ActiveBlock::Search | ActiveBlock::Switcher
+        )
     }
 
     pub fn on_key(&mut self, key: Key) {
@@ -150,11 +516,33 @@ impl App {
             self.notify.take();
             return;
         }
+        // the task switcher opens from anywhere but text entry and popups
+        if key == Key::Ctrl('p')
+            && !matches!(
+                self.active_block(),
+                ActiveBlock::Input
+                    | ActiveBlock::Notes
+                    | ActiveBlock::Search
+                    | ActiveBlock::Confirm
+                    | ActiveBlock::Switcher
+            )
+        {
+            self.switcher.open(&self.inventory.tasks_list);
+            self.push_block(ActiveBlock::Switcher);
+            return;
+        }
         match self.active_block() {
             ActiveBlock::Navitab => navi_handle(self, key),
             ActiveBlock::InventoryList => inventory_list_handle(self, key),
             ActiveBlock::TaskList => inventory_task_handle(self, key),
+            ActiveBlock::Search => task_search_handle(self, key),
+            ActiveBlock::Settings => settings_handle(self, key),
+            ActiveBlock::Heatmap => heatmap_handle(self, key),
+            ActiveBlock::Archived => archived_handle(self, key),
+            ActiveBlock::Confirm => confirm_handle(self, key),
             ActiveBlock::Input => self.input.on_key(key),
+            ActiveBlock::Notes => self.notes.on_key(key),
+            ActiveBlock::Switcher => switcher_handle(self, key),
         }
     }
 }
@@ -167,11 +555,27 @@ fn navi_handle(app: &mut App, key: Key) {
     }
 
     match app.tabs.tab_type() {
-        TabType::Tomato => match key {
-            Key::Char(' ') => app.tomato.flip(),
-            Key::Esc => app.tomato.reset(),
-            _ => {}
-        },
+        TabType::Tomato => {
+            match key {
+                Key::Char(' ') => app.tomato.flip(),
+                Key::Esc => app.tomato.reset(),
+                Key::Char('s') => app.tomato.skip(),
+                Key::Char('+') => app.tomato.extend(),
+                Key::Char('r') => app.tomato.restart(),
+                Key::Char('g') => {
+                    app.edit_goal(None);
+                    return;
+                }
+                Key::Char('x') => {
+                    if let Some((iidx, tidx)) = app.tomato.where_idx() {
+                        app.toggle_task(&app.inventory.tasks_list[iidx][tidx]);
+                    }
+                    return;
+                }
+                _ => return,
+            }
+            app.save_session();
+        }
 
         TabType::Inventory => {
             if let Key::Char('\n') = key {
@@ -179,7 +583,72 @@ fn navi_handle(app: &mut App, key: Key) {
             }
         }
 
-        TabType::Statistics => {}
+        TabType::Statistics => {
+            let range = match key {
+                Key::Char('w') => StatRange::Week,
+                Key::Char('m') => StatRange::Month,
+                Key::Char('y') => StatRange::Year,
+                // just switched to this tab
+                Key::Left | Key::Right => app.statistics.range,
+                Key::Char('\n') => {
+                    app.push_block(ActiveBlock::Heatmap);
+                    let day = app.statistics.move_day(0);
+                    app.process_handle.send(ProcessMsg::LoadDayTomatos(day));
+                    return;
+                }
+                _ => return,
+            };
+            app.statistics.range = range;
+            app.refresh_statistics();
+        }
+
+        TabType::Settings => {
+            if let Key::Char('\n') = key {
+                app.push_block(ActiveBlock::Settings);
+            }
+        }
+    }
+}
+
+fn heatmap_handle(app: &mut App, key: Key) {
+    let delta = match key {
+        Key::Up => -1,
+        Key::Down => 1,
+        Key::Left => -7,
+        Key::Right => 7,
+        Key::Esc => {
+            app.pop_block();
+            return;
+        }
+        _ => return,
+    };
+    let day = app.statistics.move_day(delta);
+    app.process_handle.send(ProcessMsg::LoadDayTomatos(day));
+}
+
+fn settings_handle(app: &mut App, key: Key) {
+    match key {
+        Key::Up => app.settings.previous(),
+        Key::Down => app.settings.next(),
+        Key::Char('\n') => {
+            let field = app.settings.selected_field();
+            if field.is_toggle() {
+                let mut config = *app.tomato.config();
+                let flipped = 1 - field.value(&config);
+                field.set(&mut config, flipped);
+                app.process_handle
+                    .send(ProcessMsg::UpdateConfig(Box::new(config)));
+                return;
+            }
+            let setting = Box::new(EditSetting {
+                field,
+                base: *app.tomato.config(),
+            });
+            app.push_block(ActiveBlock::Input);
+            app.input.set_context(InputContext::EditSetting(setting));
+        }
+        Key::Esc => app.pop_block(),
+        _ => {}
     }
 }
 
@@ -193,6 +662,15 @@ fn inventory_list_handle(app: &mut App, key: Key) {
             }
         }
         Key::Esc => app.pop_block(),
+        // search the tasks of every inventory
+        Key::Char('/') => {
+            if app.inventory.inventory_selected.is_none() {
+                app.inventory.next_inventory();
+            }
+            app.inventory.start_search(true);
+            app.push_block(ActiveBlock::TaskList);
+            app.push_block(ActiveBlock::Search);
+        }
         Key::Ctrl('n') => {
             let inv = Box::new(NewInventory::default());
             app.push_block(ActiveBlock::Input);
@@ -209,9 +687,22 @@ fn inventory_list_handle(app: &mut App, key: Key) {
         Key::Ctrl('d') => {
             if let Some(idx) = app.inventory.inventory_selected {
                 let id = app.inventory.inventory_list[idx].id;
-                app.process_handle.send(ProcessMsg::DeleteInventory(id));
+                if id == DEFAULT_ID {
+                    app.notify = Some("The default inventory can't be archived".to_owned());
+                    return;
+                }
+                app.process_handle.send(ProcessMsg::ArchiveInventory(id));
+            }
+        }
+        Key::Char('g') => {
+            if let Some(idx) = app.inventory.inventory_selected {
+                let inv = app.inventory.inventory_list[idx].clone();
+                app.edit_goal(Some(&inv));
             }
         }
+        Key::Char('a') => app.open_archive(),
+        Key::Char('u') => app.undo(),
+        Key::Ctrl('r') => app.redo(),
         _ => {}
     }
 }
@@ -222,12 +713,15 @@ fn inventory_task_handle(app: &mut App, key: Key) {
         Key::Down => app.inventory.next_task(),
         Key::Char('\n') => {
             if let Some(loc) = app.inventory.get_task_location() {
-                app.tomato.set_where_idx(loc);
-                app.reset_block();
-                app.tabs.next();
+                app.bind_task(loc);
             }
         }
+        Key::Esc if app.inventory.search.is_some() => app.inventory.clear_search(),
         Key::Esc | Key::Left => app.pop_block(),
+        Key::Char('/') => {
+            app.inventory.start_search(false);
+            app.push_block(ActiveBlock::Search);
+        }
         Key::Ctrl('n') => {
             let mut task = Box::new(NewTask::default());
             let idx = app.inventory.inventory_selected.unwrap();
@@ -243,12 +737,136 @@ fn inventory_task_handle(app: &mut App, key: Key) {
                 app.input.set_context(InputContext::EditTask(task))
             }
         }
+        Key::Char('x') => {
+            if let Some((iidx, tidx)) = app.inventory.get_task_location() {
+                app.toggle_task(&app.inventory.tasks_list[iidx][tidx]);
+            }
+        }
+        Key::Char('f') => app.inventory.next_filter(),
+        Key::Char('n') => {
+            if let Some((iidx, tidx)) = app.inventory.get_task_location() {
+                app.notes.open(&app.inventory.tasks_list[iidx][tidx]);
+                app.push_block(ActiveBlock::Notes);
+            }
+        }
+        Key::Char('e') => {
+            if let Some((iidx, tidx)) = app.inventory.get_task_location() {
+                let mut task = Box::new(EditTask::default());
+                task.id = app.inventory.tasks_list[iidx][tidx].id;
+                app.push_block(ActiveBlock::Input);
+                app.input.set_context(InputContext::EstimateTask(task))
+            }
+        }
         Key::Ctrl('d') => {
             if let Some((iidx, tidx)) = app.inventory.get_task_location() {
                 let id = app.inventory.tasks_list[iidx][tidx].id;
-                app.process_handle.send(ProcessMsg::DeleteTask(id));
+                if id == DEFAULT_ID {
+                    app.notify = Some("The default task can't be archived".to_owned());
+                    return;
+                }
+                app.process_handle.send(ProcessMsg::ArchiveTask(id));
             }
         }
+        Key::Char('a') => app.open_archive(),
+        Key::Char('u') => app.undo(),
+        Key::Ctrl('r') => app.redo(),
+        _ => {}
+    }
+}
+
+// typing narrows the task list, Enter keeps the search and Esc drops it
+fn task_search_handle(app: &mut App, key: Key) {
+    match key {
+        Key::Up => app.inventory.previous_task(),
+        Key::Down => app.inventory.next_task(),
+        Key::Char('\n') => app.pop_block(),
+        Key::Char('\t') => app.inventory.toggle_search_scope(),
+        Key::Esc => {
+            app.inventory.clear_search();
+            app.pop_block();
+        }
+        Key::Backspace => app.inventory.pop_search(),
+        Key::Char(c) => app.inventory.push_search(c),
+        _ => {}
+    }
+}
+
+fn switcher_handle(app: &mut App, key: Key) {
+    match key {
+        Key::Up => app.switcher.previous(),
+        Key::Down => app.switcher.next(),
+        Key::Char('\n') => {
+            if let Some(loc) = app.switcher.selected_location() {
+                app.bind_task(loc);
+            }
+        }
+        Key::Esc => app.pop_block(),
+        Key::Backspace => app.switcher.pop_query(&app.inventory.tasks_list),
+        Key::Char(c) => app.switcher.push_query(c, &app.inventory.tasks_list),
+        _ => {}
+    }
+}
+
+fn archived_handle(app: &mut App, key: Key) {
+    match key {
+        Key::Up => app.archive.previous(),
+        Key::Down => app.archive.next(),
+        Key::Char('\n') | Key::Char('r') => match app.archive.selected_item() {
+            Some(ArchivedItem::Inventory(inv)) => app
+                .process_handle
+                .send(ProcessMsg::RestoreInventory(inv.id)),
+            Some(ArchivedItem::Task(task, _)) => {
+                app.process_handle.send(ProcessMsg::RestoreTask(task.id))
+            }
+            None => {}
+        },
+        // purge for good, tomatos of it are gone too
+        Key::Ctrl('d') => {
+            let confirm = match app.archive.selected_item() {
+                Some(ArchivedItem::Inventory(inv)) => Confirmation::new(
+                    format!("Purge inventory {:?}?", inv.name),
+                    ProcessMsg::DeleteInventory(inv.id),
+                ),
+                Some(ArchivedItem::Task(task, _)) => Confirmation::new(
+                    format!("Purge task {:?}?", task.name),
+                    ProcessMsg::DeleteTask(task.id),
+                ),
+                None => return,
+            };
+            app.process_handle
+                .send(ProcessMsg::Confirm(Box::new(confirm)));
+        }
+        Key::Char('u') => app.undo(),
+        Key::Ctrl('r') => app.redo(),
+        Key::Esc => app.pop_block(),
+        _ => {}
+    }
+}
+
+fn confirm_handle(app: &mut App, key: Key) {
+    match key {
+        // enter only resumes, a purge cannot be undone so it asks for y
+        Key::Char('\n')
+            if !matches!(
+                app.confirm.as_deref(),
+                Some(Confirmation {
+                    action: ConfirmAction::Resume(_),
+                    ..
+                })
+            ) => {}
+        Key::Char('y') | Key::Char('\n') => {
+            if let Some(confirm) = app.confirm.take() {
+                match confirm.action {
+                    ConfirmAction::Send(msg) => app.process_handle.send(msg),
+                    ConfirmAction::Resume(session) => app.resume(&session),
+                }
+            }
+            app.pop_block();
+        }
+        Key::Char('n') | Key::Esc => {
+            app.confirm = None;
+            app.pop_block();
+        }
         _ => {}
     }
 }
@@ -258,5 +876,12 @@ pub enum ActiveBlock {
     Navitab,
     InventoryList,
     TaskList,
+    Settings,
+    Heatmap,
+    Archived,
+    Confirm,
     Input,
+    Notes,
+    Search,
+    Switcher,
 }
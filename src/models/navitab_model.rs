@@ -3,6 +3,7 @@ pub enum TabType {
     Inventory = 0,
     Tomato = 1,
     Statistics = 2,
+    Settings = 3,
 }
 
 pub struct NavitabModel {
@@ -16,6 +17,7 @@ impl NavitabModel {
             "Inventory".to_owned(),
             "Tomato".to_owned(),
             "Statistics".to_owned(),
+            "Settings".to_owned(),
         ];
         NavitabModel { titles, select: 0 }
     }
@@ -36,6 +38,7 @@ impl NavitabModel {
             0 => TabType::Inventory,
             1 => TabType::Tomato,
             2 => TabType::Statistics,
+            3 => TabType::Settings,
             _ => TabType::Inventory,
         }
     }
@@ -0,0 +1,233 @@
+use crate::{
+    db::{EditTask, Task},
+    process::{ProcessHandle, ProcessMsg},
+};
+
+use super::{AppHandle, AppMsg, Key};
+
+// notes being edited, the cursor is (line, char) in lines
+pub struct NotesDraft {
+    pub task_name: String,
+    task_id: i32,
+    pub lines: Vec<String>,
+    pub row: usize,
+    pub col: usize,
+}
+
+impl NotesDraft {
+    // byte offset of the cursor in its line
+    fn offset(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(self.col)
+            .map(|(offset, _)| offset)
+            .unwrap_or_else(|| line.len())
+    }
+
+    fn line_chars(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn insert(&mut self, c: char) {
+        let offset = self.offset();
+        if c == '\n' {
+            let rest = self.lines[self.row].split_off(offset);
+            self.row += 1;
+            self.col = 0;
+            self.lines.insert(self.row, rest);
+        } else {
+            self.lines[self.row].insert(offset, c);
+            self.col += 1;
+        }
+    }
+
+    // joins the line with the previous one at its start
+    fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let offset = self.offset();
+            self.lines[self.row].remove(offset);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_chars(self.row);
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    fn move_cursor(&mut self, key: Key) {
+        match key {
+            Key::Left if self.col > 0 => self.col -= 1,
+            Key::Left if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.line_chars(self.row);
+            }
+            Key::Right if self.col < self.line_chars(self.row) => self.col += 1,
+            Key::Right if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = 0;
+            }
+            Key::Up if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.col.min(self.line_chars(self.row));
+            }
+            Key::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = self.col.min(self.line_chars(self.row));
+            }
+            Key::Home => self.col = 0,
+            Key::End => self.col = self.line_chars(self.row),
+            _ => {}
+        }
+    }
+
+    // None if only whitespace is left
+    fn text(&self) -> Option<String> {
+        let text = self.lines.join("\n");
+        if text.trim().is_empty() {
+            None
+        } else {
+            Some(text.trim_end().to_owned())
+        }
+    }
+}
+
+/// A multi-line editor of task notes: Enter breaks lines, Ctrl-s saves and Esc cancels
+pub struct NotesModel {
+    proc_hdl: ProcessHandle,
+    app_hdl: AppHandle,
+    draft: Option<NotesDraft>,
+}
+
+impl NotesModel {
+    pub fn new(app: AppHandle, proc: ProcessHandle) -> Self {
+        NotesModel {
+            app_hdl: app,
+            proc_hdl: proc,
+            draft: None,
+        }
+    }
+
+    pub fn draft(&self) -> Option<&NotesDraft> {
+        self.draft.as_ref()
+    }
+
+    // the cursor starts at the end
+    pub fn open(&mut self, task: &Task) {
+        let mut lines: Vec<String> = task
+            .notes
+            .as_deref()
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        let row = lines.len() - 1;
+        let col = lines[row].chars().count();
+        self.draft = Some(NotesDraft {
+            task_name: task.name.clone(),
+            task_id: task.id,
+            lines,
+            row,
+            col,
+        });
+    }
+
+    pub fn on_key(&mut self, key: Key) {
+        let draft = match self.draft.as_mut() {
+            Some(draft) => draft,
+            None => return,
+        };
+        match key {
+            Key::Ctrl('s') => {
+                let edit = EditTask {
+                    id: draft.task_id,
+                    notes: Some(draft.text()),
+                    ..Default::default()
+                };
+                self.draft = None;
+                self.app_hdl.send(AppMsg::InputEnd);
+                self.proc_hdl.send(ProcessMsg::UpdateTask(Box::new(edit)));
+            }
+            Key::Esc => {
+                self.draft = None;
+                self.app_hdl.send(AppMsg::InputEnd);
+            }
+            Key::Char(c) => draft.insert(c),
+            Key::Backspace => draft.backspace(),
+            key => draft.move_cursor(key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(lines: &[&str], row: usize, col: usize) -> NotesDraft {
+        NotesDraft {
+            task_name: String::new(),
+            task_id: 0,
+            lines: lines.iter().map(|l| l.to_string()).collect(),
+            row,
+            col,
+        }
+    }
+
+    #[test]
+    fn newline_splits_line() {
+        let mut d = draft(&["abcd"], 0, 2);
+        d.insert('\n');
+        assert_eq!(d.lines, vec!["ab", "cd"]);
+        assert_eq!((d.row, d.col), (1, 0));
+    }
+
+    #[test]
+    fn backspace_joins_lines() {
+        let mut d = draft(&["ab", "cd"], 1, 0);
+        d.backspace();
+        assert_eq!(d.lines, vec!["abcd"]);
+        assert_eq!((d.row, d.col), (0, 2));
+        // nothing before the start
+        let mut d = draft(&["ab"], 0, 0);
+        d.backspace();
+        assert_eq!(d.lines, vec!["ab"]);
+    }
+
+    #[test]
+    fn cursor_moves_over_cjk_chars() {
+        let mut d = draft(&["未指定"], 0, 3);
+        d.move_cursor(Key::Left);
+        d.insert('x');
+        assert_eq!(d.lines, vec!["未指x定"]);
+        assert_eq!(d.col, 3);
+
+        d.move_cursor(Key::Home);
+        d.move_cursor(Key::Right);
+        d.backspace();
+        assert_eq!(d.lines, vec!["指x定"]);
+        assert_eq!(d.col, 0);
+
+        d.insert('\n');
+        assert_eq!(d.lines, vec!["", "指x定"]);
+        d.move_cursor(Key::End);
+        assert_eq!((d.row, d.col), (1, 3));
+        d.move_cursor(Key::Up);
+        assert_eq!((d.row, d.col), (0, 0));
+        d.move_cursor(Key::Left);
+        assert_eq!((d.row, d.col), (0, 0));
+        d.move_cursor(Key::Right);
+        assert_eq!((d.row, d.col), (1, 0));
+    }
+
+    #[test]
+    fn text_trims_the_end() {
+        assert_eq!(
+            draft(&["a", "b ", ""], 0, 0).text(),
+            Some("a\nb".to_owned())
+        );
+        assert_eq!(draft(&[" ", ""], 0, 0).text(), None);
+    }
+}
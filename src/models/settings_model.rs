@@ -0,0 +1,113 @@
+use super::TomatoConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingField {
+    TaskDuration,
+    ShortBreakDuration,
+    LongBreakDuration,
+    LongBreakInterval,
+    AutoStartBreaks,
+    AutoStartFocus,
+    AutoStartDelay,
+}
+
+impl SettingField {
+    pub const ALL: &'static [SettingField] = &[
+        SettingField::TaskDuration,
+        SettingField::ShortBreakDuration,
+        SettingField::LongBreakDuration,
+        SettingField::LongBreakInterval,
+        SettingField::AutoStartBreaks,
+        SettingField::AutoStartFocus,
+        SettingField::AutoStartDelay,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            SettingField::TaskDuration => "Focus duration",
+            SettingField::ShortBreakDuration => "Short break duration",
+            SettingField::LongBreakDuration => "Long break duration",
+            SettingField::LongBreakInterval => "Long break interval",
+            SettingField::AutoStartBreaks => "Auto-start breaks",
+            SettingField::AutoStartFocus => "Auto-start focus",
+            SettingField::AutoStartDelay => "Auto-start delay",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            SettingField::LongBreakInterval => "focus",
+            SettingField::AutoStartDelay => "sec",
+            _ => "min",
+        }
+    }
+
+    // on/off, flipped by Enter instead of an input
+    pub fn is_toggle(&self) -> bool {
+        matches!(
+            self,
+            SettingField::AutoStartBreaks | SettingField::AutoStartFocus
+        )
+    }
+
+    // value in the unit showed to users
+    pub fn value(&self, config: &TomatoConfig) -> u64 {
+        match self {
+            SettingField::TaskDuration => config.task_duration / 60,
+            SettingField::ShortBreakDuration => config.short_break_duration / 60,
+            SettingField::LongBreakDuration => config.long_break_duration / 60,
+            SettingField::LongBreakInterval => config.long_break_interval as u64,
+            SettingField::AutoStartBreaks => config.auto_start_breaks as u64,
+            SettingField::AutoStartFocus => config.auto_start_focus as u64,
+            SettingField::AutoStartDelay => config.auto_start_delay,
+        }
+    }
+
+    pub fn set(&self, config: &mut TomatoConfig, value: u64) {
+        match self {
+            SettingField::TaskDuration => config.task_duration = value.saturating_mul(60),
+            SettingField::ShortBreakDuration => {
+                config.short_break_duration = value.saturating_mul(60)
+            }
+            SettingField::LongBreakDuration => {
+                config.long_break_duration = value.saturating_mul(60)
+            }
+            SettingField::LongBreakInterval => config.long_break_interval = value as usize,
+            SettingField::AutoStartBreaks => config.auto_start_breaks = value != 0,
+            SettingField::AutoStartFocus => config.auto_start_focus = value != 0,
+            SettingField::AutoStartDelay => config.auto_start_delay = value,
+        }
+    }
+}
+
+// trace: SettingsModel -> InputModel -> IO process(validate & persist) -> App
+pub struct EditSetting {
+    pub field: SettingField,
+    pub base: TomatoConfig,
+}
+
+pub struct SettingsModel {
+    pub selected: usize,
+}
+
+impl SettingsModel {
+    pub fn new() -> Self {
+        SettingsModel { selected: 0 }
+    }
+
+    pub fn selected_field(&self) -> SettingField {
+        SettingField::ALL[self.selected]
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % SettingField::ALL.len();
+    }
+
+    pub fn previous(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+        } else {
+            self.selected = SettingField::ALL.len() - 1;
+        }
+    }
+}
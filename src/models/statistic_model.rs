@@ -0,0 +1,294 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, Local, NaiveDate, TimeZone};
+
+use crate::db::{DayFocus, DbError, DbUtils, EstimateAccuracy, InventoryFocus, TomatoDetail};
+
+// days covered by the heatmap
+pub const HEATMAP_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatRange {
+    Week,
+    Month,
+    Year,
+}
+
+impl StatRange {
+    pub const ALL: &'static [StatRange] = &[StatRange::Week, StatRange::Month, StatRange::Year];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            StatRange::Week => "Week",
+            StatRange::Month => "Month",
+            StatRange::Year => "Year",
+        }
+    }
+
+    // the first date included by the range
+    fn since(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            StatRange::Week => today - chrono::Duration::days(6),
+            StatRange::Month => today - chrono::Duration::days(29),
+            StatRange::Year => months_ago(today, 11),
+        }
+    }
+}
+
+fn months_ago(date: NaiveDate, n: i32) -> NaiveDate {
+    let months = date.year() * 12 + date.month0() as i32 - n;
+    NaiveDate::from_ymd(months / 12, months as u32 % 12 + 1, 1)
+}
+
+pub fn local_timestamp(date: NaiveDate) -> i64 {
+    Local
+        .from_local_datetime(&date.and_hms(0, 0, 0))
+        .earliest()
+        .map(|t| t.timestamp())
+        .unwrap_or(0)
+}
+
+// e.g. "1h 5m", shared by the tui and the cli
+pub fn format_duration(secs: i64) -> String {
+    let mins = secs / 60;
+    if mins >= 60 {
+        format!("{}h {}m", mins / 60, mins % 60)
+    } else {
+        format!("{}m", mins)
+    }
+}
+
+fn parse_day(day: &DayFocus) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&day.day, "%Y-%m-%d").ok()
+}
+
+pub struct Statistics {
+    pub range: StatRange,
+    // (label, focus minutes)
+    pub bars: Vec<(String, u64)>,
+    pub inventories: Vec<InventoryFocus>,
+    pub total_count: i64,
+    // focus sessions including interrupted and skipped ones
+    pub total_sessions: i64,
+    pub total_seconds: i64,
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    // tomato count of each day in the last HEATMAP_DAYS
+    pub day_counts: HashMap<NaiveDate, i64>,
+    pub estimates: EstimateAccuracy,
+}
+
+impl Statistics {
+    pub fn load(range: StatRange) -> Result<Self, DbError> {
+        let today = Local::today().naive_local();
+        let since = range.since(today);
+        let ts = local_timestamp(since);
+
+        let days = DbUtils::focus_per_day(ts)?;
+        let seconds: HashMap<NaiveDate, i64> = days
+            .iter()
+            .filter_map(|d| parse_day(d).map(|date| (date, d.seconds)))
+            .collect();
+
+        let bars = match range {
+            StatRange::Week | StatRange::Month => since
+                .iter_days()
+                .take_while(|d| *d <= today)
+                .map(|d| {
+                    let label = match range {
+                        StatRange::Week => d.format("%a").to_string(),
+                        _ => d.format("%d").to_string(),
+                    };
+                    (label, *seconds.get(&d).unwrap_or(&0) as u64 / 60)
+                })
+                .collect(),
+            StatRange::Year => (0..12)
+                .rev()
+                .map(|n| {
+                    let month = months_ago(today, n);
+                    let secs: i64 = seconds
+                        .iter()
+                        .filter(|(d, _)| d.year() == month.year() && d.month() == month.month())
+                        .map(|(_, s)| s)
+                        .sum();
+                    (month.format("%b").to_string(), secs as u64 / 60)
+                })
+                .collect(),
+        };
+
+        let all_days = DbUtils::focus_per_day(0)?;
+        let (current_streak, longest_streak) = streaks(today, &all_days);
+        let heatmap_since = today - chrono::Duration::days(HEATMAP_DAYS - 1);
+        let day_counts = all_days
+            .iter()
+            .filter_map(|d| parse_day(d).map(|date| (date, d.count)))
+            .filter(|(date, _)| *date >= heatmap_since)
+            .collect();
+
+        Ok(Statistics {
+            range,
+            bars,
+            inventories: DbUtils::focus_per_inventory(ts)?,
+            total_count: days.iter().map(|d| d.count).sum(),
+            total_sessions: days.iter().map(|d| d.sessions).sum(),
+            total_seconds: days.iter().map(|d| d.seconds).sum(),
+            current_streak,
+            longest_streak,
+            day_counts,
+            estimates: DbUtils::estimate_accuracy(ts)?,
+        })
+    }
+
+    // percentage of focus sessions completed
+    pub fn completion_rate(&self) -> Option<i64> {
+        if self.total_sessions == 0 {
+            return None;
+        }
+        Some(self.total_count * 100 / self.total_sessions)
+    }
+
+    // percentage of estimated tasks not over their estimates
+    pub fn within_estimate_rate(&self) -> Option<i64> {
+        if self.estimates.tasks == 0 {
+            return None;
+        }
+        Some(self.estimates.within * 100 / self.estimates.tasks)
+    }
+
+    // tomatoes spent per tomato estimated
+    pub fn estimate_ratio(&self) -> Option<f64> {
+        if self.estimates.estimated == 0 {
+            return None;
+        }
+        Some(self.estimates.spent as f64 / self.estimates.estimated as f64)
+    }
+}
+
+// (current, longest) consecutive days with at least one tomato.
+// The current streak is kept if today has nothing yet but yesterday does.
+fn streaks(today: NaiveDate, days: &[DayFocus]) -> (usize, usize) {
+    let dates: HashSet<NaiveDate> = days
+        .iter()
+        .filter(|d| d.count > 0)
+        .filter_map(parse_day)
+        .collect();
+
+    let mut day = today;
+    if !dates.contains(&day) {
+        day = day.pred();
+    }
+    let mut current = 0;
+    while dates.contains(&day) {
+        current += 1;
+        day = day.pred();
+    }
+
+    let mut sorted: Vec<_> = dates.into_iter().collect();
+    sorted.sort_unstable();
+    let (mut longest, mut run) = (0, 0);
+    for (idx, date) in sorted.iter().enumerate() {
+        if idx > 0 && sorted[idx - 1].succ() == *date {
+            run += 1;
+        } else {
+            run = 1;
+        }
+        longest = longest.max(run);
+    }
+
+    (current, longest)
+}
+
+pub fn tomatos_of_day(day: NaiveDate) -> Result<Vec<TomatoDetail>, DbError> {
+    DbUtils::tomatos_between(local_timestamp(day), local_timestamp(day.succ()))
+}
+
+pub struct StatisticsModel {
+    pub range: StatRange,
+    pub data: Option<Box<Statistics>>,
+    // cursor on the heatmap
+    pub day_selected: Option<NaiveDate>,
+    pub day_tomatos: Vec<TomatoDetail>,
+}
+
+impl StatisticsModel {
+    pub fn new() -> Self {
+        StatisticsModel {
+            range: StatRange::Week,
+            data: None,
+            day_selected: None,
+            day_tomatos: Vec::new(),
+        }
+    }
+
+    /// move the heatmap cursor by `delta` days, limited in the heatmap
+    pub fn move_day(&mut self, delta: i64) -> NaiveDate {
+        self.move_day_from(Local::today().naive_local(), delta)
+    }
+
+    fn move_day_from(&mut self, today: NaiveDate, delta: i64) -> NaiveDate {
+        let first = today - chrono::Duration::days(HEATMAP_DAYS - 1);
+        let day = self.day_selected.unwrap_or(today) + chrono::Duration::days(delta);
+        let day = day.max(first).min(today);
+        if self.day_selected != Some(day) {
+            self.day_tomatos.clear();
+        }
+        self.day_selected = Some(day);
+        day
+    }
+
+    pub fn set_day_tomatos(&mut self, day: NaiveDate, tomatos: Vec<TomatoDetail>) {
+        if self.day_selected == Some(day) {
+            self.day_tomatos = tomatos;
+        }
+    }
+
+    pub fn set_data(&mut self, data: Box<Statistics>) {
+        // a late response of a previous range is useless
+        if data.range == self.range {
+            self.data = Some(data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{DbColor, Outcome};
+
+    #[test]
+    fn move_day_stays_in_heatmap() {
+        let today = NaiveDate::from_ymd(2026, 10, 17);
+        let mut model = StatisticsModel::new();
+        assert_eq!(model.move_day_from(today, 0), today);
+        assert_eq!(model.move_day_from(today, 1), today);
+        assert_eq!(
+            model.move_day_from(today, -7),
+            NaiveDate::from_ymd(2026, 10, 10)
+        );
+
+        let first = today - chrono::Duration::days(HEATMAP_DAYS - 1);
+        assert_eq!(model.move_day_from(today, -400), first);
+        assert_eq!(model.move_day_from(today, -1), first);
+        assert_eq!(model.move_day_from(today, 1), first.succ());
+    }
+
+    #[test]
+    fn move_day_drops_tomatos_of_another_day() {
+        let today = NaiveDate::from_ymd(2026, 10, 17);
+        let mut model = StatisticsModel::new();
+        let day = model.move_day_from(today, 0);
+        let tomato = TomatoDetail {
+            start_time: 0,
+            end_time: 0,
+            task: String::new(),
+            inventory: String::new(),
+            color: DbColor::DARK_GRAY,
+            outcome: Outcome::Completed,
+        };
+        model.set_day_tomatos(day, vec![tomato]);
+        model.move_day_from(today, 0);
+        assert_eq!(model.day_tomatos.len(), 1);
+        model.move_day_from(today, -1);
+        assert!(model.day_tomatos.is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::db::{DbError, DbUtils, Task};
+
+use super::inventory_model::fuzzy_match;
+
+const RECENT_TASKS: i32 = 10;
+
+/// A task of the switcher with the chars of its name matching the query
+pub struct SwitcherItem {
+    pub location: (usize, usize),
+    pub positions: Vec<usize>,
+    pub recent: bool,
+}
+
+/// open tasks matching the query, the closest matches first and the recent
+/// ones first among them, so an empty query lists the recent tasks on top
+fn rank_tasks(query: &str, recent: &[i32], tasks_list: &[Vec<Task>]) -> Vec<SwitcherItem> {
+    let mut items: Vec<_> = tasks_list
+        .iter()
+        .enumerate()
+        .flat_map(|(iidx, tasks)| {
+            tasks
+                .iter()
+                .enumerate()
+                .map(move |(tidx, task)| ((iidx, tidx), task))
+        })
+        .filter(|(_, task)| task.completed_at.is_none())
+        .filter_map(|(location, task)| {
+            let positions = fuzzy_match(query, &task.name)?;
+            let rank = recent.iter().position(|id| *id == task.id);
+            // how far apart the matched chars are
+            let spread = match (positions.first(), positions.last()) {
+                (Some(first), Some(last)) => last - first,
+                _ => 0,
+            };
+            let item = SwitcherItem {
+                location,
+                positions,
+                recent: rank.is_some(),
+            };
+            Some(((spread, rank.unwrap_or(usize::MAX)), item))
+        })
+        .collect();
+    items.sort_by_key(|(key, _)| *key);
+    items.into_iter().map(|(_, item)| item).collect()
+}
+
+/// The quick task switcher, open tasks of every inventory to bind to the timer
+pub struct SwitcherModel {
+    pub query: String,
+    pub selected: usize,
+    // task ids, the latest bound or worked on first
+    recent: Vec<i32>,
+    // ranked for the query, see `refresh`
+    items: Vec<SwitcherItem>,
+}
+
+impl SwitcherModel {
+    pub fn new() -> Result<Self, DbError> {
+        Ok(SwitcherModel {
+            query: String::new(),
+            selected: 0,
+            recent: DbUtils::recent_task_ids(RECENT_TASKS)?,
+            items: Vec::new(),
+        })
+    }
+
+    pub fn open(&mut self, tasks_list: &[Vec<Task>]) {
+        self.query.clear();
+        self.selected = 0;
+        self.refresh(tasks_list);
+    }
+
+    /// rank the tasks again, after the query or the tasks changed
+    pub fn refresh(&mut self, tasks_list: &[Vec<Task>]) {
+        self.items = rank_tasks(&self.query, &self.recent, tasks_list);
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+    }
+
+    pub fn touch(&mut self, task_id: i32) {
+        self.recent.retain(|id| *id != task_id);
+        self.recent.insert(0, task_id);
+        self.recent.truncate(RECENT_TASKS as usize);
+    }
+
+    pub fn push_query(&mut self, c: char, tasks_list: &[Vec<Task>]) {
+        self.query.push(c);
+        self.selected = 0;
+        self.refresh(tasks_list);
+    }
+
+    pub fn pop_query(&mut self, tasks_list: &[Vec<Task>]) {
+        self.query.pop();
+        self.selected = 0;
+        self.refresh(tasks_list);
+    }
+
+    pub fn next(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + 1) % self.items.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        let len = self.items.len();
+        if len > 0 {
+            self.selected = (self.selected + len - 1) % len;
+        }
+    }
+
+    pub fn items(&self) -> &[SwitcherItem] {
+        &self.items
+    }
+
+    pub fn selected_location(&self) -> Option<(usize, usize)> {
+        self.items.get(self.selected).map(|item| item.location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (id, name) of open tasks in one inventory each
+    fn tasks(inventories: &[&[(i32, &str)]]) -> Vec<Vec<Task>> {
+        inventories
+            .iter()
+            .map(|tasks| {
+                tasks
+                    .iter()
+                    .map(|(id, name)| Task {
+                        id: *id,
+                        name: name.to_string(),
+                        ..Default::default()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn locations(items: &[SwitcherItem]) -> Vec<(usize, usize)> {
+        items.iter().map(|item| item.location).collect()
+    }
+
+    #[test]
+    fn empty_query_lists_recent_first() {
+        let tasks = tasks(&[&[(1, "report"), (2, "email")], &[(3, "rust")]]);
+        let items = rank_tasks("", &[3, 2], &tasks);
+        assert_eq!(locations(&items), vec![(1, 0), (0, 1), (0, 0)]);
+        assert!(items[0].recent && items[1].recent && !items[2].recent);
+    }
+
+    #[test]
+    fn closer_matches_before_recent_ones() {
+        let tasks = tasks(&[&[(1, "rust"), (2, "read us"), (3, "rust book")]]);
+        // "rus" is contiguous in both rust tasks, spread apart in "read us"
+        let items = rank_tasks("rus", &[2, 3], &tasks);
+        assert_eq!(locations(&items), vec![(0, 2), (0, 0), (0, 1)]);
+        assert_eq!(items[0].positions, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn completed_and_unmatched_tasks_left_out() {
+        let mut tasks = tasks(&[&[(1, "report"), (2, "email"), (3, "review")]]);
+        tasks[0][2].completed_at = Some(0);
+        let items = rank_tasks("re", &[], &tasks);
+        assert_eq!(locations(&items), vec![(0, 0)]);
+    }
+}
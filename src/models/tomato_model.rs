@@ -1,31 +1,95 @@
 use std::time::{Duration, Instant};
 
+use serde::{Deserialize, Serialize};
 use tui::style::{Color, Style};
 
-use crate::{db::Tomato, process::ProcessHandle};
-
-use super::AppHandle;
-
-#[derive(Debug, Clone, Copy)]
+use crate::{
+    config::ConfigError,
+    db::{Outcome, Session, Tomato},
+    hooks::{Hook, HookEvent},
+    notifier::{Notifier, NotifyConfig},
+    process::ProcessHandle,
+};
+
+use super::{AppHandle, AppMsg};
+
+// the countdown widget can't display more than 99 minutes
+const MAX_DURATION: u64 = 99 * 60;
+// a focus reset before this many seconds is not worth a record
+const MIN_RECORDED_FOCUS: u64 = 60;
+// added to the running countdown by each extend
+const EXTEND_DURATION: u64 = 5 * 60;
+const MAX_AUTO_START_DELAY: u64 = 10 * 60;
+// the cycle of phases is built from it
+const MAX_LONG_BREAK_INTERVAL: usize = 99;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct TomatoConfig {
-    // seconds
-    task_duration: u64,
-    short_break_duration: u64,
-    long_break_duration: u64,
+    // seconds, minutes in config file
+    #[serde(with = "crate::config::minutes")]
+    pub task_duration: u64,
+    #[serde(with = "crate::config::minutes")]
+    pub short_break_duration: u64,
+    #[serde(with = "crate::config::minutes")]
+    pub long_break_duration: u64,
     // count
-    long_break_interval: usize,
+    pub long_break_interval: usize,
+    // start the next phase without Space
+    pub auto_start_breaks: bool,
+    pub auto_start_focus: bool,
+    // seconds to wait before an auto start
+    pub auto_start_delay: u64,
 }
 
 impl Default for TomatoConfig {
     fn default() -> Self {
         TomatoConfig {
-            task_duration: 5,
-            short_break_duration: 3,
-            long_break_duration: 10,
-            long_break_interval: 3,
+            task_duration: 25 * 60,
+            short_break_duration: 5 * 60,
+            long_break_duration: 15 * 60,
+            long_break_interval: 4,
+            auto_start_breaks: false,
+            auto_start_focus: false,
+            auto_start_delay: 0,
+        }
+    }
+}
+
+impl TomatoConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let durations = [
+            ("timer.task_duration", self.task_duration),
+            ("timer.short_break_duration", self.short_break_duration),
+            ("timer.long_break_duration", self.long_break_duration),
+        ];
+        for (field, secs) in durations.iter() {
+            if *secs == 0 || *secs > MAX_DURATION {
+                return Err(ConfigError::Invalid(
+                    field,
+                    format!("expect 1 to 99 minutes, got {}", secs / 60),
+                ));
+            }
+        }
+        if self.long_break_interval == 0 || self.long_break_interval > MAX_LONG_BREAK_INTERVAL {
+            return Err(ConfigError::Invalid(
+                "timer.long_break_interval",
+                format!(
+                    "expect 1 to 99 focuses before a long break, got {}",
+                    self.long_break_interval
+                ),
+            ));
         }
+        if self.auto_start_delay > MAX_AUTO_START_DELAY {
+            return Err(ConfigError::Invalid(
+                "timer.auto_start_delay",
+                format!("expect 0 to 600 seconds, got {}", self.auto_start_delay),
+            ));
+        }
+        Ok(())
     }
 }
+
 #[derive(Default)]
 struct TomatoContext {
     where_idx: Option<(usize, usize)>,
@@ -53,11 +117,67 @@ impl State {
     pub fn current(&self) -> CountdownType {
         self.states[self.idx]
     }
+
+    fn long_break_interval(&self) -> usize {
+        self.states.len() / 2
+    }
+
+    // back to the focus that starts the current focus/break pair
+    fn back_to_focus(&mut self) {
+        self.idx -= self.idx % 2;
+    }
+
+    // as saved in a session, the index is kept in the cycle
+    fn restore(long_break_interval: i32, idx: i32) -> Self {
+        let interval = (long_break_interval.max(1) as usize).min(MAX_LONG_BREAK_INTERVAL);
+        let mut state = State::new(interval);
+        state.idx = idx.max(0) as usize % state.states.len();
+        state
+    }
+}
+
+fn secs(secs: i64) -> Duration {
+    Duration::from_secs(secs.max(0) as u64)
+}
+
+// time passed since the session was saved
+fn closed_duration(session: &Session, now: i64) -> Duration {
+    secs(now - session.saved_at)
+}
+
+/// What resuming the session would bring back, e.g. "Focus, 12:34 left, paused"
+pub fn describe_session(session: &Session) -> String {
+    describe_session_at(session, chrono::Utc::now().timestamp())
+}
+
+fn describe_session_at(session: &Session, now: i64) -> String {
+    let phase = State::restore(session.long_break_interval, session.phase_idx).current();
+    let mut left = secs(session.left_seconds);
+    if !session.paused && session.started_at.is_some() {
+        left = left.saturating_sub(closed_duration(session, now));
+    }
+    if left == Duration::ZERO {
+        return format!("{}, over while closed", phase.title());
+    }
+    let secs = left.as_secs();
+    let state = match (session.started_at, session.paused) {
+        (None, _) => "not started",
+        (Some(_), true) => "paused",
+        (Some(_), false) => "running",
+    };
+    format!(
+        "{}, {:02}:{:02} left, {}",
+        phase.title(),
+        secs / 60,
+        secs % 60,
+        state
+    )
 }
 
 pub struct TomatoModel {
     handle: AppHandle,
-    _process: ProcessHandle,
+    process: ProcessHandle,
+    notifier: Notifier,
     context: TomatoContext,
     state: State,
     countdown: Countdown,
@@ -70,14 +190,41 @@ enum CountdownType {
     LongBreak,
 }
 
+impl CountdownType {
+    fn title(&self) -> &'static str {
+        match self {
+            CountdownType::Focus => "Focus",
+            CountdownType::ShortBreak => "Short break",
+            CountdownType::LongBreak => "Long break",
+        }
+    }
+
+    fn slug(&self) -> &'static str {
+        match self {
+            CountdownType::Focus => "focus",
+            CountdownType::ShortBreak => "short-break",
+            CountdownType::LongBreak => "long-break",
+        }
+    }
+}
+
 impl TomatoModel {
-    pub fn new(handle: AppHandle, process: ProcessHandle) -> Self {
-        let context = TomatoContext::default();
+    pub fn new(
+        handle: AppHandle,
+        process: ProcessHandle,
+        config: TomatoConfig,
+        notify: &NotifyConfig,
+    ) -> Self {
+        let context = TomatoContext {
+            config,
+            ..Default::default()
+        };
         let state = State::new(context.config.long_break_interval);
 
         let mut tomato = TomatoModel {
             handle,
-            _process: process,
+            notifier: Notifier::new(notify, &process),
+            process,
             context,
             state,
             countdown: Countdown::new(Duration::ZERO),
@@ -102,8 +249,16 @@ impl TomatoModel {
         self.countdown = Countdown::new(left).color(Color::Green);
     }
 
-    fn switch_countdown(&mut self) {
-        match self.state.next() {
+    fn phase_duration(&self, phase: CountdownType) -> u64 {
+        match phase {
+            CountdownType::Focus => self.context.config.task_duration,
+            CountdownType::ShortBreak => self.context.config.short_break_duration,
+            CountdownType::LongBreak => self.context.config.long_break_duration,
+        }
+    }
+
+    fn set_phase(&mut self, phase: CountdownType) {
+        match phase {
             CountdownType::Focus => {
                 self.set_focus();
             }
@@ -116,26 +271,144 @@ impl TomatoModel {
         }
     }
 
+    fn switch_countdown(&mut self) {
+        let ended = self.state.current();
+        self.state.next();
+        // a changed long_break_interval takes effect from a new cycle
+        let interval = self.context.config.long_break_interval;
+        if self.state.idx == 0 && self.state.long_break_interval() != interval {
+            self.state = State::new(interval);
+        }
+        self.set_phase(self.state.current());
+
+        let next = self.state.current();
+        self.notifier.notify(&format!(
+            "{} is over, {} for {} min",
+            ended.title(),
+            next.title().to_lowercase(),
+            self.phase_duration(next) / 60
+        ));
+
+        let config = &self.context.config;
+        let auto_start = match self.state.current() {
+            CountdownType::Focus => config.auto_start_focus,
+            _ => config.auto_start_breaks,
+        };
+        if auto_start {
+            self.countdown.auto_start =
+                Some(Instant::now() + Duration::from_secs(config.auto_start_delay));
+        }
+    }
+
+    // None if the countdown is as fresh as a launch makes it
+    pub fn session(&self, task_id: Option<i32>) -> Option<Session> {
+        let countdown = &self.countdown;
+        if countdown.started_at.is_none() && self.state.idx == 0 && task_id.is_none() {
+            return None;
+        }
+        Some(Session {
+            id: 0,
+            phase_idx: self.state.idx as i32,
+            long_break_interval: self.state.long_break_interval() as i32,
+            duration: countdown.duration.as_secs() as i64,
+            left_seconds: countdown.left.as_secs() as i64,
+            paused: countdown.paused,
+            task_id,
+            started_at: countdown.started_at,
+            paused_seconds: countdown.paused_duration().as_secs() as i64,
+            saved_at: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    pub fn resume(&mut self, session: &Session) {
+        self.state = State::restore(session.long_break_interval, session.phase_idx);
+        self.set_phase(self.state.current());
+        self.countdown
+            .restore(session, chrono::Utc::now().timestamp());
+    }
+
+    pub fn phase_idx(&self) -> usize {
+        self.state.idx
+    }
+
+    pub fn config(&self) -> &TomatoConfig {
+        &self.context.config
+    }
+
+    // reached goals are told the same way as phase changes
+    pub fn notify(&mut self, message: &str) {
+        self.notifier.notify(message);
+    }
+
+    pub fn set_notify_config(&mut self, notify: &NotifyConfig) {
+        self.notifier = Notifier::new(notify, &self.process);
+    }
+
+    pub fn set_config(&mut self, config: TomatoConfig) {
+        let phase = self.state.current();
+        let untouched = self.countdown.paused
+            && self.countdown.left == Duration::from_secs(self.phase_duration(phase));
+        self.context.config = config;
+        // a running countdown keeps its duration, the new one is used from the next phase
+        if untouched {
+            self.set_phase(phase);
+        }
+    }
+
     // 生成一个 tomato row，用以发送给 process 写入数据库
-    pub fn tomato_complete(&self) {
+    fn close_focus(&self, outcome: Outcome) {
         let end_time = chrono::Utc::now().timestamp();
-        let start_time = end_time - self.context.config.task_duration as i64;
         self.handle.close_tomato(Box::new(Tomato {
             inventory_id: 0,
             task_id: 0,
-            start_time,
+            start_time: self.countdown.started_at.unwrap_or(end_time),
             end_time,
+            outcome: outcome as i32,
+            paused_seconds: self.countdown.paused_duration().as_secs() as i64,
         }));
     }
 
+    // hooks are run by the App, which knows the task names
+    fn phase_hook(&self, end: bool) {
+        let phase = self.state.current();
+        let event = match (phase, end) {
+            (CountdownType::Focus, false) => HookEvent::FocusStart,
+            (CountdownType::Focus, true) => HookEvent::FocusEnd,
+            (_, false) => HookEvent::BreakStart,
+            (_, true) => HookEvent::BreakEnd,
+        };
+        self.handle.send(AppMsg::Hook(Box::new(Hook {
+            event,
+            phase: phase.slug(),
+            duration: self.countdown.duration.as_secs(),
+            elapsed: self.countdown.elapsed().as_secs(),
+            inventory: String::new(),
+            task: String::new(),
+        })));
+    }
+
+    // a phase never started has nothing to end
+    fn end_phase(&self) {
+        if self.countdown.started_at.is_some() {
+            self.phase_hook(true);
+        }
+    }
+
     pub fn on_tick(&mut self) {
         if self.countdown.is_exhausted() {
             if self.state.current() == CountdownType::Focus {
-                self.tomato_complete();
+                self.close_focus(Outcome::Completed);
             }
+            self.end_phase();
             self.switch_countdown();
         }
+        let started = self.countdown.started_at.is_some();
         self.countdown.on_tick();
+        // auto started
+        if !started && self.countdown.started_at.is_some() {
+            self.phase_hook(false);
+        }
+        self.notifier.on_tick();
     }
 
     pub fn min_and_sec(&self) -> (u64, u64) {
@@ -150,34 +423,88 @@ impl TomatoModel {
         self.context.where_idx = Some(loc);
     }
 
+    // the bound task is gone, following tomatos go to the default task
+    pub fn clear_where_idx(&mut self) {
+        self.context.where_idx = None;
+    }
+
     pub fn flip(&mut self) {
+        let started = self.countdown.started_at.is_some();
         self.countdown.flip();
+        if !started {
+            self.phase_hook(false);
+        }
     }
 
     pub fn fg_style(&self) -> Style {
         self.countdown.color
     }
 
+    // the focus done so far is kept as an interrupted tomato
+    fn interrupt_focus(&self) {
+        if self.state.current() == CountdownType::Focus
+            && self.countdown.elapsed().as_secs() >= MIN_RECORDED_FOCUS
+        {
+            self.close_focus(Outcome::Interrupted);
+        }
+    }
+
     pub fn reset(&mut self) {
+        self.interrupt_focus();
+        self.end_phase();
+        self.state.back_to_focus();
         self.set_focus();
     }
+
+    pub fn restart(&mut self) {
+        self.interrupt_focus();
+        self.end_phase();
+        self.set_phase(self.state.current());
+    }
+
+    // go to the next phase now, the cycle goes on as if the phase were done
+    pub fn skip(&mut self) {
+        if self.state.current() == CountdownType::Focus && self.countdown.started_at.is_some() {
+            self.close_focus(Outcome::Skipped);
+        }
+        self.end_phase();
+        self.switch_countdown();
+    }
+
+    pub fn extend(&mut self) {
+        self.countdown.extend(Duration::from_secs(EXTEND_DURATION));
+    }
 }
 
 #[derive(Debug, Clone)]
 struct Countdown {
+    duration: Duration,
     left: Duration,
     tickpoint: Instant,
     color: Style,
     paused: bool,
+    // unix time of the first resume, None if never started
+    started_at: Option<i64>,
+    // (paused, resumed) after started, resumed is None while paused
+    pauses: Vec<(Instant, Option<Instant>)>,
+    // paused before the app was restarted
+    paused_before: Duration,
+    // when to start by itself
+    auto_start: Option<Instant>,
 }
 
 impl Default for Countdown {
     fn default() -> Self {
         Countdown {
+            duration: Duration::ZERO,
             left: Duration::ZERO,
             tickpoint: Instant::now(),
             color: Style::default(),
             paused: true,
+            started_at: None,
+            pauses: Vec::new(),
+            paused_before: Duration::ZERO,
+            auto_start: None,
         }
     }
 }
@@ -185,6 +512,7 @@ impl Default for Countdown {
 impl Countdown {
     fn new(left: Duration) -> Self {
         Countdown {
+            duration: left,
             left,
             ..Default::default()
         }
@@ -200,6 +528,10 @@ impl Countdown {
             return;
         }
 
+        if matches!(self.auto_start, Some(at) if at <= Instant::now()) {
+            self.flip();
+        }
+
         if !self.paused {
             self.left = self.left.saturating_sub(self.tickpoint.elapsed());
         }
@@ -207,16 +539,220 @@ impl Countdown {
         self.tickpoint = Instant::now();
     }
 
+    fn elapsed(&self) -> Duration {
+        self.duration.saturating_sub(self.left)
+    }
+
+    // never beyond what the widget can display
+    fn extend(&mut self, by: Duration) {
+        let by = by.min(Duration::from_secs(MAX_DURATION).saturating_sub(self.left));
+        self.duration += by;
+        self.left += by;
+    }
+
     fn is_exhausted(&self) -> bool {
         self.left == Duration::ZERO
     }
 
     fn flip(&mut self) {
+        self.auto_start = None;
+        let now = Instant::now();
+        if !self.paused {
+            self.pauses.push((now, None));
+        } else if self.started_at.is_none() {
+            self.started_at = Some(chrono::Utc::now().timestamp());
+        } else if let Some((_, resumed)) = self.pauses.last_mut() {
+            *resumed = Some(now);
+        }
         self.paused = !self.paused;
     }
 
+    // time spent paused since started, an ongoing pause counts up to now
+    fn paused_duration(&self) -> Duration {
+        self.paused_before
+            + self
+                .pauses
+                .iter()
+                .map(|(paused, resumed)| resumed.unwrap_or_else(Instant::now) - *paused)
+                .sum::<Duration>()
+    }
+
+    // the time the app was closed is counted down, or counted as paused if it was paused
+    fn restore(&mut self, session: &Session, now: i64) {
+        self.duration = secs(session.duration);
+        self.left = secs(session.left_seconds);
+        self.started_at = session.started_at;
+        self.paused_before = secs(session.paused_seconds);
+        if session.started_at.is_none() {
+            return;
+        }
+        let closed = closed_duration(session, now);
+        if session.paused {
+            self.paused_before += closed;
+            self.pauses.push((Instant::now(), None));
+        } else {
+            // a phase over while closed ends on the next tick, its overtime is not focus
+            self.paused_before += closed.saturating_sub(self.left);
+            self.left = self.left.saturating_sub(closed);
+            self.paused = false;
+        }
+    }
+
     fn min_and_sec(&self) -> (u64, u64) {
         let secs = self.left.as_secs();
         (secs / 60, secs % 60)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_break_interval_bounds() {
+        let config = |long_break_interval| TomatoConfig {
+            long_break_interval,
+            ..Default::default()
+        };
+        assert!(config(1).validate().is_ok());
+        assert!(config(99).validate().is_ok());
+        assert!(config(0).validate().is_err());
+        assert!(config(100).validate().is_err());
+        assert!(config(usize::MAX).validate().is_err());
+    }
+
+    #[test]
+    fn back_to_focus_of_the_pair() {
+        let mut state = State::new(3);
+        state.back_to_focus();
+        assert_eq!((state.idx, state.current()), (0, CountdownType::Focus));
+        assert_eq!(state.next(), CountdownType::ShortBreak);
+        state.back_to_focus();
+        assert_eq!((state.idx, state.current()), (0, CountdownType::Focus));
+        state.idx = 5;
+        assert_eq!(state.current(), CountdownType::LongBreak);
+        state.back_to_focus();
+        assert_eq!((state.idx, state.current()), (4, CountdownType::Focus));
+    }
+
+    fn session(paused: bool, started: bool) -> Session {
+        Session {
+            id: 0,
+            phase_idx: 0,
+            long_break_interval: 4,
+            duration: 1500,
+            left_seconds: 600,
+            paused,
+            task_id: None,
+            started_at: if started { Some(900) } else { None },
+            paused_seconds: 60,
+            saved_at: 1000,
+        }
+    }
+
+    fn restored(session: &Session, now: i64) -> Countdown {
+        let mut countdown = Countdown::new(Duration::from_secs(1500));
+        countdown.restore(session, now);
+        countdown
+    }
+
+    #[test]
+    fn closed_time_counts_down_while_running() {
+        let countdown = restored(&session(false, true), 1100);
+        assert!(!countdown.paused);
+        assert_eq!(countdown.left, secs(500));
+        assert_eq!(countdown.started_at, Some(900));
+        assert_eq!(countdown.paused_duration(), secs(60));
+    }
+
+    #[test]
+    fn closed_time_counts_as_pause_while_paused() {
+        let countdown = restored(&session(true, true), 1100);
+        assert!(countdown.paused);
+        assert_eq!(countdown.left, secs(600));
+        // the ongoing pause was just pushed, it adds no more than a moment
+        assert!(countdown.paused_duration() - secs(160) < secs(1));
+    }
+
+    #[test]
+    fn overtime_while_closed_is_not_focus() {
+        let countdown = restored(&session(false, true), 2000);
+        assert!(countdown.is_exhausted());
+        assert_eq!(countdown.elapsed(), secs(1500));
+        // closed for 1000s with 600s left, the other 400s were not focus
+        assert_eq!(countdown.paused_duration(), secs(460));
+    }
+
+    #[test]
+    fn not_started_ignores_closed_time() {
+        let countdown = restored(&session(false, false), 5000);
+        assert!(countdown.paused);
+        assert_eq!(countdown.left, secs(600));
+        assert_eq!(countdown.paused_duration(), secs(60));
+    }
+
+    #[test]
+    fn closed_time_is_never_negative() {
+        assert_eq!(closed_duration(&session(false, true), 900), Duration::ZERO);
+        assert_eq!(closed_duration(&session(false, true), 1030), secs(30));
+    }
+
+    #[test]
+    fn restore_keeps_the_index_in_the_cycle() {
+        let state = State::restore(4, 9);
+        assert_eq!((state.idx, state.current()), (1, CountdownType::ShortBreak));
+        let state = State::restore(2, 3);
+        assert_eq!(state.current(), CountdownType::LongBreak);
+        let state = State::restore(0, -1);
+        assert_eq!((state.long_break_interval(), state.idx), (1, 0));
+        let state = State::restore(1000, 0);
+        assert_eq!(state.long_break_interval(), MAX_LONG_BREAK_INTERVAL);
+    }
+
+    #[test]
+    fn paused_spans_add_up() {
+        let mut countdown = Countdown::new(secs(1500));
+        countdown.flip();
+        assert!(!countdown.paused && countdown.started_at.is_some());
+        assert!(countdown.pauses.is_empty());
+        countdown.flip();
+        assert!(countdown.paused);
+        assert!(matches!(countdown.pauses.as_slice(), [(_, None)]));
+        countdown.flip();
+        assert!(matches!(countdown.pauses.as_slice(), [(_, Some(_))]));
+
+        let now = Instant::now();
+        countdown.paused_before = secs(60);
+        countdown.pauses = vec![
+            (now - secs(30), Some(now - secs(20))),
+            (now - secs(5), Some(now)),
+        ];
+        assert_eq!(countdown.paused_duration(), secs(75));
+    }
+
+    #[test]
+    fn describe_a_saved_session() {
+        assert_eq!(
+            describe_session_at(&session(false, true), 1100),
+            "Focus, 08:20 left, running"
+        );
+        assert_eq!(
+            describe_session_at(&session(true, true), 5000),
+            "Focus, 10:00 left, paused"
+        );
+        assert_eq!(
+            describe_session_at(&session(false, false), 5000),
+            "Focus, 10:00 left, not started"
+        );
+        assert_eq!(
+            describe_session_at(&session(false, true), 2000),
+            "Focus, over while closed"
+        );
+        let mut long_break = session(true, true);
+        long_break.phase_idx = 7;
+        assert_eq!(
+            describe_session_at(&long_break, 1000),
+            "Long break, 10:00 left, paused"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::VecDeque;
+
+use crate::db::{EditInventory, EditTask, Inventory, Purged, Task};
+
+// operations kept for undo, older ones are dropped
+const UNDO_LIMIT: usize = 20;
+
+// an applied operation, with what it takes to revert and reapply it
+pub enum Operation {
+    ArchiveInventory(Inventory),
+    ArchiveTask(Task),
+    Purge(Purged),
+    EditInventory {
+        before: EditInventory,
+        after: EditInventory,
+    },
+    EditTask {
+        before: EditTask,
+        after: EditTask,
+    },
+}
+
+impl Operation {
+    pub fn describe(&self) -> String {
+        match self {
+            Operation::ArchiveInventory(inv) => format!("archive inventory {:?}", inv.name),
+            Operation::ArchiveTask(task) => format!("archive task {:?}", task.name),
+            Operation::Purge(purged) => match (&purged.inventory, purged.tasks.first()) {
+                (Some(inv), _) => format!("purge inventory {:?}", inv.name),
+                (None, Some(task)) => format!("purge task {:?}", task.name),
+                (None, None) => "purge".to_owned(),
+            },
+            Operation::EditInventory { before, after } => match (&before.name, &after.name) {
+                (Some(old), Some(new)) if old != new => {
+                    format!("rename inventory {:?} to {:?}", old, new)
+                }
+                _ => "edit inventory color".to_owned(),
+            },
+            Operation::EditTask { before, after } => {
+                match (&before.name, &after.name, after.completed_at) {
+                    (Some(old), Some(new), _) => format!("rename task {:?} to {:?}", old, new),
+                    (_, _, Some(Some(_))) => "complete task".to_owned(),
+                    (_, _, Some(None)) => "reopen task".to_owned(),
+                    _ if after.notes.is_some() => "edit task notes".to_owned(),
+                    _ => "edit task estimate".to_owned(),
+                }
+            }
+        }
+    }
+}
+
+// session only, nothing is persisted
+#[derive(Default)]
+pub struct UndoModel {
+    undo: VecDeque<Operation>,
+    redo: Vec<Operation>,
+}
+
+impl UndoModel {
+    // a new operation makes the undone ones unreachable
+    pub fn push(&mut self, op: Operation) {
+        self.redo.clear();
+        self.push_undo(op);
+    }
+
+    pub fn push_undo(&mut self, op: Operation) {
+        if self.undo.len() == UNDO_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(op);
+    }
+
+    pub fn push_redo(&mut self, op: Operation) {
+        self.redo.push(op);
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Operation> {
+        self.undo.pop_back()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Operation> {
+        self.redo.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(id: i32) -> Operation {
+        let task = EditTask {
+            id,
+            ..Default::default()
+        };
+        Operation::EditTask {
+            before: task.clone(),
+            after: task,
+        }
+    }
+
+    fn id_of(op: Option<Operation>) -> Option<i32> {
+        match op? {
+            Operation::EditTask { after, .. } => Some(after.id),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn oldest_dropped_over_limit() {
+        let mut model = UndoModel::default();
+        for id in 0..UNDO_LIMIT as i32 + 3 {
+            model.push(edit(id));
+        }
+        for id in (3..UNDO_LIMIT as i32 + 3).rev() {
+            assert_eq!(id_of(model.pop_undo()), Some(id));
+        }
+        assert!(model.pop_undo().is_none());
+    }
+
+    #[test]
+    fn push_clears_redo() {
+        let mut model = UndoModel::default();
+        model.push(edit(1));
+        let op = model.pop_undo().unwrap();
+        model.push_redo(op);
+        model.push(edit(2));
+        assert!(model.pop_redo().is_none());
+        assert_eq!(id_of(model.pop_undo()), Some(2));
+    }
+
+    #[test]
+    fn undo_redo_undo_order() {
+        let mut model = UndoModel::default();
+        model.push(edit(1));
+        model.push(edit(2));
+
+        // undo both, the latest first
+        let op = model.pop_undo().unwrap();
+        model.push_redo(op);
+        let op = model.pop_undo().unwrap();
+        model.push_redo(op);
+        assert!(model.pop_undo().is_none());
+
+        // redo brings back the earliest first
+        let op = model.pop_redo().unwrap();
+        assert_eq!(id_of(Some(op)), Some(1));
+        model.push_undo(edit(1));
+
+        // and the next undo takes it again, 2 is still to redo
+        assert_eq!(id_of(model.pop_undo()), Some(1));
+        assert_eq!(id_of(model.pop_redo()), Some(2));
+    }
+}
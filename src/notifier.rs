@@ -0,0 +1,193 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::process::{ProcessHandle, ProcessMsg};
+
+// how long the screen stays in reverse video
+const FLASH_DURATION: Duration = Duration::from_millis(150);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyConfig {
+    pub bell: bool,
+    pub flash: bool,
+    // freedesktop notification over D-Bus
+    pub desktop: bool,
+    // run by `sh -c`, with the message in $TOMATO_MESSAGE
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        NotifyConfig {
+            bell: true,
+            flash: false,
+            desktop: false,
+            command: None,
+        }
+    }
+}
+
+/// A way to tell the user that a phase is over
+pub trait Notify {
+    fn notify(&mut self, message: &str);
+
+    // for notifications lasting a while
+    fn on_tick(&mut self) {}
+}
+
+fn write_terminal(bytes: &[u8]) {
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(bytes);
+    let _ = stdout.flush();
+}
+
+struct Bell;
+
+impl Notify for Bell {
+    fn notify(&mut self, _: &str) {
+        write_terminal(b"\x07");
+    }
+}
+
+struct Flash {
+    off_at: Option<Instant>,
+}
+
+impl Notify for Flash {
+    fn notify(&mut self, _: &str) {
+        write_terminal(b"\x1b[?5h");
+        self.off_at = Some(Instant::now() + FLASH_DURATION);
+    }
+
+    fn on_tick(&mut self) {
+        if matches!(self.off_at, Some(at) if at <= Instant::now()) {
+            self.off_at = None;
+            write_terminal(b"\x1b[?5l");
+        }
+    }
+}
+
+struct Desktop {
+    process: ProcessHandle,
+}
+
+// a GVariant text format string literal, unlike Rust Debug escapes
+fn gvariant_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('\'');
+    for c in s.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\'' => quoted.push_str("\\'"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+impl Notify for Desktop {
+    fn notify(&mut self, message: &str) {
+        // gdbus comes with glib, so there is no need to link libdbus.
+        // String arguments are GVariant literals.
+        let mut cmd = Command::new("gdbus");
+        cmd.args([
+            "call",
+            "--session",
+            "--dest=org.freedesktop.Notifications",
+            "--object-path=/org/freedesktop/Notifications",
+            "--method=org.freedesktop.Notifications.Notify",
+            "'tomato'",
+            "0",
+            "''",
+            "'tomato'",
+            &gvariant_string(message),
+            "[]",
+            "{}",
+            "5000",
+        ]);
+        self.process.send(ProcessMsg::RunCommand(
+            "desktop notification",
+            Box::new(cmd),
+        ));
+    }
+}
+
+struct Shell {
+    command: String,
+    process: ProcessHandle,
+}
+
+impl Notify for Shell {
+    fn notify(&mut self, message: &str) {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(&self.command)
+            .env("TOMATO_MESSAGE", message);
+        self.process
+            .send(ProcessMsg::RunCommand("notify command", Box::new(cmd)));
+    }
+}
+
+pub struct Notifier {
+    sinks: Vec<Box<dyn Notify>>,
+}
+
+impl Notifier {
+    pub fn new(config: &NotifyConfig, process: &ProcessHandle) -> Self {
+        let mut sinks: Vec<Box<dyn Notify>> = Vec::new();
+        if config.bell {
+            sinks.push(Box::new(Bell));
+        }
+        if config.flash {
+            sinks.push(Box::new(Flash { off_at: None }));
+        }
+        if config.desktop {
+            sinks.push(Box::new(Desktop {
+                process: process.clone(),
+            }));
+        }
+        if let Some(command) = &config.command {
+            sinks.push(Box::new(Shell {
+                command: command.clone(),
+                process: process.clone(),
+            }));
+        }
+        Notifier { sinks }
+    }
+
+    pub fn notify(&mut self, message: &str) {
+        for sink in self.sinks.iter_mut() {
+            sink.notify(message);
+        }
+    }
+
+    pub fn on_tick(&mut self) {
+        for sink in self.sinks.iter_mut() {
+            sink.on_tick();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gvariant_string_escapes() {
+        assert_eq!(gvariant_string("Focus done"), "'Focus done'");
+        assert_eq!(gvariant_string("it's a \"goal\""), "'it\\'s a \"goal\"'");
+        assert_eq!(gvariant_string("a\\b\nc"), "'a\\\\b\\nc'");
+        assert_eq!(gvariant_string("\u{1b}[1m"), "'\\u001b[1m'");
+        assert_eq!(gvariant_string("未指定 🍅"), "'未指定 🍅'");
+    }
+}
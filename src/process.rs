@@ -1,7 +1,22 @@
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+use std::process::Stdio;
+
+use futures::FutureExt;
+use tokio::process::Command;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
-use crate::db::{DbUtils, EditInventory, EditTask, NewInventory, NewTask, Tomato};
-use crate::models::{AppHandle, AppMsg};
+use crate::config::{Config, ConfigError};
+use crate::db::{
+    DbError, DbUtils, EditInventory, EditTask, Goal, NewInventory, NewTask, Outcome, Session,
+    Tomato,
+};
+use crate::models::{
+    tomatos_of_day, AppHandle, AppMsg, ArchivedItem, Confirmation, GoalProgress, Operation,
+    StatRange, Statistics, TomatoConfig,
+};
+
+use chrono::NaiveDate;
 
 pub enum ProcessMsg {
     TomatoClose(Box<Tomato>),
@@ -11,6 +26,23 @@ pub enum ProcessMsg {
     CreateTask(Box<NewTask>),
     DeleteTask(i32),
     UpdateTask(Box<EditTask>),
+    UpdateConfig(Box<TomatoConfig>),
+    LoadStatistics(StatRange),
+    LoadDayTomatos(NaiveDate),
+    ArchiveInventory(i32),
+    ArchiveTask(i32),
+    RestoreInventory(i32),
+    RestoreTask(i32),
+    LoadArchived,
+    Undo(Box<Operation>),
+    Redo(Box<Operation>),
+    Confirm(Box<Confirmation>),
+    // (what it is for, command)
+    RunCommand(&'static str, Box<Command>),
+    // None clears the saved one
+    SaveSession(Option<Box<Session>>),
+    LoadGoals,
+    SetGoal(Box<Goal>),
 }
 
 #[derive(Clone)]
@@ -18,7 +50,11 @@ pub struct ProcessHandle {
     sender: UnboundedSender<ProcessMsg>,
 }
 
-#[tokio::main]
+// panics of this thread are caught and notified, see Process::run
+pub const PROCESS_THREAD: &str = "process";
+
+// commands spawned by the worker run on its own thread too
+#[tokio::main(flavor = "current_thread")]
 async fn run_process(mut process: Process) {
     process.run().await;
 }
@@ -32,7 +68,10 @@ impl ProcessHandle {
             app_handle,
         };
 
-        std::thread::spawn(move || run_process(process));
+        std::thread::Builder::new()
+            .name(PROCESS_THREAD.to_owned())
+            .spawn(move || run_process(process))
+            .expect("failed to spawn the process thread");
 
         ProcessHandle { sender }
     }
@@ -54,46 +93,266 @@ struct Process {
 impl Process {
     async fn run(&mut self) {
         while let Some(msg) = self.receiver.recv().await {
-            self.process_message(msg).await;
+            // a panic here should not take the worker down with it
+            let result = AssertUnwindSafe(self.process_message(msg))
+                .catch_unwind()
+                .await;
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => self.app_handle.notify(e.to_string()),
+                Err(panic) => self.app_handle.notify(panic_message(panic)),
+            }
         }
     }
 
-    async fn process_message(&mut self, msg: ProcessMsg) {
+    async fn process_message(&mut self, msg: ProcessMsg) -> Result<(), DbError> {
         use ProcessMsg::*;
         match msg {
-            TomatoClose(t) => handle_tomota_close(t).await,
+            // a completed tomato may reach a goal
+            TomatoClose(t) => {
+                handle_tomota_close(t).await?;
+                self.load_goals()?;
+            }
             CreateInventory(inv) => {
-                let inv = DbUtils::create_new_inventory(&inv.name, inv.color);
+                let inv = DbUtils::create_new_inventory(&inv.name, inv.color)?;
                 self.app_handle.send(AppMsg::NewInventory(Box::new(inv)));
             }
             CreateTask(task) => {
                 let task =
-                    DbUtils::create_new_task(task.inventory_id, &task.name, task.notes.as_deref());
+                    DbUtils::create_new_task(task.inventory_id, &task.name, task.notes.as_deref())?;
                 self.app_handle.send(AppMsg::NewTask(Box::new(task)));
             }
             DeleteInventory(id) => {
-                DbUtils::delete_inventory(id);
+                let purged = DbUtils::delete_inventory(id)?;
                 self.app_handle.send(AppMsg::DeleteInventory(id));
+                self.done(Operation::Purge(purged));
             }
             DeleteTask(id) => {
-                DbUtils::delete_task(id);
+                let purged = DbUtils::delete_task(id)?;
                 self.app_handle.send(AppMsg::DeleteTask(id));
+                self.done(Operation::Purge(purged));
             }
             UpdateInventory(inv) => {
-                DbUtils::edit_inventory(&*inv);
-                self.app_handle.send(AppMsg::EditInventory(inv))
+                let before = DbUtils::edit_inventory(&inv)?;
+                self.app_handle.send(AppMsg::EditInventory(inv.clone()));
+                self.done(Operation::EditInventory {
+                    before,
+                    after: *inv,
+                });
             }
             UpdateTask(task) => {
-                DbUtils::edit_task(&*task);
-                self.app_handle.send(AppMsg::EditTask(task))
+                let before = DbUtils::edit_task(&task)?;
+                self.app_handle.send(AppMsg::EditTask(task.clone()));
+                self.done(Operation::EditTask {
+                    before,
+                    after: *task,
+                });
+            }
+            UpdateConfig(timer) => match save_timer_config(*timer) {
+                Ok(config) => self.app_handle.send(AppMsg::UpdateConfig(Box::new(config))),
+                Err(e) => self.app_handle.notify(e.to_string()),
+            },
+            LoadStatistics(range) => {
+                let stat = Statistics::load(range)?;
+                self.app_handle.send(AppMsg::Statistics(Box::new(stat)));
+            }
+            LoadDayTomatos(day) => {
+                let tomatos = tomatos_of_day(day)?;
+                self.app_handle.send(AppMsg::DayTomatos(day, tomatos));
+            }
+            // archived rows leave the lists just like deleted ones
+            ArchiveInventory(id) => {
+                let inv = DbUtils::archive_inventory(id)?;
+                self.app_handle.send(AppMsg::DeleteInventory(id));
+                self.done(Operation::ArchiveInventory(inv));
+            }
+            ArchiveTask(id) => {
+                let task = DbUtils::archive_task(id)?;
+                self.app_handle.send(AppMsg::DeleteTask(id));
+                self.done(Operation::ArchiveTask(task));
+            }
+            RestoreInventory(id) => {
+                let (inv, tasks) = DbUtils::restore_inventory(id)?;
+                self.app_handle
+                    .send(AppMsg::RestoreInventory(Box::new(inv), tasks));
+            }
+            RestoreTask(id) => {
+                let task = DbUtils::restore_task(id)?;
+                self.app_handle.send(AppMsg::RestoreTask(Box::new(task)));
+            }
+            LoadArchived => {
+                let items = ArchivedItem::load_all()?;
+                self.app_handle.send(AppMsg::Archived(items));
+            }
+            // a failed operation goes back to the stack it came from
+            Undo(op) => {
+                if let Err(e) = self.revert(&op) {
+                    self.app_handle.send(AppMsg::UndoFailed(op));
+                    return Err(e);
+                }
+                self.app_handle.send(AppMsg::Undone(op));
+            }
+            Redo(op) => {
+                if let Err(e) = self.reapply(&op) {
+                    self.app_handle.send(AppMsg::RedoFailed(op));
+                    return Err(e);
+                }
+                self.app_handle.send(AppMsg::Redone(op));
+            }
+            Confirm(mut confirm) => {
+                confirm.count_affected()?;
+                self.app_handle.send(AppMsg::Confirm(confirm));
+            }
+            RunCommand(what, mut cmd) => {
+                let app_handle = self.app_handle.clone();
+                // slow commands should not hold up the worker
+                tokio::spawn(async move {
+                    if let Err(e) = run_command(&mut cmd).await {
+                        app_handle.notify(format!("{} failed: {}", what, e));
+                    }
+                });
+            }
+            SaveSession(session) => DbUtils::save_session(session.as_deref())?,
+            LoadGoals => self.load_goals()?,
+            SetGoal(goal) => {
+                DbUtils::set_goal(&goal)?;
+                self.load_goals()?;
             }
         }
+        Ok(())
+    }
+
+    fn load_goals(&self) -> Result<(), DbError> {
+        let progress = GoalProgress::load()?;
+        self.app_handle.send(AppMsg::Goals(Box::new(progress)));
+        Ok(())
+    }
+
+    fn done(&self, op: Operation) {
+        self.app_handle.send(AppMsg::Done(Box::new(op)));
     }
+
+    fn revert(&self, op: &Operation) -> Result<(), DbError> {
+        match op {
+            Operation::ArchiveInventory(inv) => {
+                let (inv, tasks) = DbUtils::restore_inventory(inv.id)?;
+                self.app_handle
+                    .send(AppMsg::RestoreInventory(Box::new(inv), tasks));
+            }
+            Operation::ArchiveTask(task) => {
+                let task = DbUtils::restore_task(task.id)?;
+                self.app_handle.send(AppMsg::RestoreTask(Box::new(task)));
+            }
+            Operation::Purge(purged) => {
+                DbUtils::revive(purged)?;
+                match (&purged.inventory, purged.tasks.first()) {
+                    (Some(inv), _) if inv.archived_at.is_none() => {
+                        let tasks = purged
+                            .tasks
+                            .iter()
+                            .filter(|t| t.archived_at.is_none())
+                            .cloned()
+                            .collect();
+                        self.app_handle
+                            .send(AppMsg::RestoreInventory(Box::new(inv.clone()), tasks));
+                    }
+                    (None, Some(task)) if task.archived_at.is_none() => {
+                        self.app_handle
+                            .send(AppMsg::RestoreTask(Box::new(task.clone())));
+                    }
+                    // back to the archive
+                    _ => {
+                        let items = ArchivedItem::load_all()?;
+                        self.app_handle.send(AppMsg::Archived(items));
+                    }
+                }
+            }
+            Operation::EditInventory { before, .. } => {
+                DbUtils::edit_inventory(before)?;
+                self.app_handle
+                    .send(AppMsg::EditInventory(Box::new(before.clone())));
+            }
+            Operation::EditTask { before, .. } => {
+                DbUtils::edit_task(before)?;
+                self.app_handle
+                    .send(AppMsg::EditTask(Box::new(before.clone())));
+            }
+        }
+        Ok(())
+    }
+
+    fn reapply(&self, op: &Operation) -> Result<(), DbError> {
+        match op {
+            Operation::ArchiveInventory(inv) => {
+                DbUtils::archive_inventory(inv.id)?;
+                self.app_handle.send(AppMsg::DeleteInventory(inv.id));
+            }
+            Operation::ArchiveTask(task) => {
+                DbUtils::archive_task(task.id)?;
+                self.app_handle.send(AppMsg::DeleteTask(task.id));
+            }
+            Operation::Purge(purged) => match (&purged.inventory, purged.tasks.first()) {
+                (Some(inv), _) => {
+                    DbUtils::delete_inventory(inv.id)?;
+                    self.app_handle.send(AppMsg::DeleteInventory(inv.id));
+                }
+                (None, Some(task)) => {
+                    DbUtils::delete_task(task.id)?;
+                    self.app_handle.send(AppMsg::DeleteTask(task.id));
+                }
+                (None, None) => {}
+            },
+            Operation::EditInventory { after, .. } => {
+                DbUtils::edit_inventory(after)?;
+                self.app_handle
+                    .send(AppMsg::EditInventory(Box::new(after.clone())));
+            }
+            Operation::EditTask { after, .. } => {
+                DbUtils::edit_task(after)?;
+                self.app_handle
+                    .send(AppMsg::EditTask(Box::new(after.clone())));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    let msg = match panic.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(panic) => match panic.downcast::<&str>() {
+            Ok(msg) => msg.to_string(),
+            Err(_) => "unknown".to_owned(),
+        },
+    };
+    format!("internal error: {}", msg)
+}
+
+// output is captured, it would mess up the screen
+async fn run_command(cmd: &mut Command) -> Result<(), String> {
+    let output = cmd
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(format!("{} {}", output.status, stderr.trim()))
+}
+
+fn save_timer_config(timer: TomatoConfig) -> Result<Config, ConfigError> {
+    timer.validate()?;
+    // sections not editable in app are kept as they are in the file
+    let mut config = Config::load()?;
+    config.timer = timer;
+    config.save_timer()?;
+    Ok(config)
 }
 
-async fn handle_tomota_close(tomato: Box<Tomato>) {
+pub async fn handle_tomota_close(tomato: Box<Tomato>) -> Result<(), DbError> {
     let tomato = *tomato;
-    let delta_spent = tomato.end_time - tomato.start_time;
-    DbUtils::update_task_spent(tomato.task_id, delta_spent);
-    DbUtils::create_new_tomato(tomato);
+    let completed = Outcome::from(tomato.outcome) == Outcome::Completed;
+    DbUtils::create_new_tomato(tomato, completed as i32)
 }
@@ -1,16 +1,31 @@
-use super::widgets::Countdown;
-use crate::models::{ActiveBlock, App, InputContext, TabType};
+use super::widgets::{Countdown, Heatmap};
+use crate::db::{DbColor, Outcome};
+use crate::models::{
+    format_duration, ActiveBlock, App, ArchivedItem, Confirmation, GoalState, InputContext,
+    NotesDraft, SettingField, StatRange, Statistics, TabType,
+};
 
 use tui::backend::Backend;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use tui::style::{Color, Modifier, Style};
 use tui::text::{Span, Spans};
-use tui::widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs};
+use tui::widgets::{
+    BarChart, Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap,
+};
 use tui::Frame;
 
 use chrono::TimeZone;
 use unicode_width::UnicodeWidthStr;
 
+// heatmap colors from no tomato to the most tomatos of a day
+const HEATMAP_PALETTE: &[DbColor] = &[
+    DbColor::DARK_GRAY,
+    DbColor::indexed(22),
+    DbColor::indexed(28),
+    DbColor::indexed(34),
+    DbColor::indexed(40),
+];
+
 const COUNTDOWN_SIZES: &[(u16, u16, u16, u16)] = &[
     (7, 41, 0, 0),
     (13, 50, 7, 41),
@@ -20,6 +35,19 @@ const COUNTDOWN_SIZES: &[(u16, u16, u16, u16)] = &[
     (u16::MAX, u16::MAX, 15, 91),
 ];
 
+// e.g. "3/8", green once reached
+fn goal_span(state: &GoalState) -> Span<'static> {
+    let color = if state.is_reached() {
+        Color::Green
+    } else {
+        Color::DarkGray
+    };
+    Span::styled(
+        format!("{}/{}", state.done, state.target),
+        Style::default().fg(color),
+    )
+}
+
 fn thick_border_or_not(app: &App, target_block: ActiveBlock) -> BorderType {
     if app.active_block() == target_block {
         BorderType::Thick
@@ -67,12 +95,29 @@ pub fn draw_app<B: Backend>(f: &mut Frame<B>, app: &App) {
         TabType::Inventory => draw_inventory_tab(f, app, chunks[1]),
         TabType::Tomato => draw_tomato_tab(f, app, chunks[1]),
         TabType::Statistics => draw_statistic_tab(f, app, chunks[1]),
+        TabType::Settings => draw_settings_tab(f, app, chunks[1]),
     };
 
+    if app.is_block_open(ActiveBlock::Archived) {
+        draw_archive(f, app, f.size());
+    }
+
     if app.input.is_active() {
         draw_input(f, app, f.size());
     }
 
+    if let Some(draft) = app.notes.draft() {
+        draw_notes_editor(f, draft, f.size());
+    }
+
+    if app.is_block_open(ActiveBlock::Switcher) {
+        draw_switcher(f, app, f.size());
+    }
+
+    if let Some(confirm) = &app.confirm {
+        draw_confirm(f, confirm, f.size());
+    }
+
     if let Some(msg) = &app.notify {
         draw_popup(f, msg, f.size());
     }
@@ -94,6 +139,9 @@ fn draw_input<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
                 InputContext::NewTask(_) => "New task",
                 InputContext::EditInventory(_) => "Edit inventory entry",
                 InputContext::EditTask(_) => "Edit task",
+                InputContext::EstimateTask(_) => "Estimate: tomatoes, 0 for none",
+                InputContext::EditSetting(s) => s.field.title(),
+                InputContext::EditGoal(g) => &g.title,
             })
             .border_type(BorderType::Rounded)
             .border_style(Style::default().fg(Color::Yellow))
@@ -103,6 +151,110 @@ fn draw_input<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     f.set_cursor(area.x + app.input.content().width() as u16 + 1, area.y + 1);
 }
 
+fn draw_notes_editor<B: Backend>(f: &mut Frame<B>, draft: &NotesDraft, area: Rect) {
+    let area = centered_rect(60, 60, area);
+    f.render_widget(Clear, area);
+    // keep the cursor in sight, 2 borderlines each way
+    let height = area.height.saturating_sub(2) as usize;
+    let width = area.width.saturating_sub(2) as usize;
+    let scroll = (draft.row + 1).saturating_sub(height);
+    let before: String = draft.lines[draft.row].chars().take(draft.col).collect();
+    let scroll_x = (before.width() + 1).saturating_sub(width);
+
+    let lines: Vec<_> = draft
+        .lines
+        .iter()
+        .map(|l| Spans::from(l.as_str()))
+        .collect();
+    let paragraph = Paragraph::new(lines)
+        .scroll((scroll as u16, scroll_x as u16))
+        .block(
+            Block::default()
+                .title(format!(
+                    "Notes of {} · ^s save, Esc cancel",
+                    draft.task_name
+                ))
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Yellow))
+                .borders(Borders::all()),
+        );
+    f.render_widget(paragraph, area);
+
+    let cursor_x = before
+        .width()
+        .saturating_sub(scroll_x)
+        .min(width.saturating_sub(1));
+    f.set_cursor(
+        area.x + 1 + cursor_x as u16,
+        area.y + 1 + (draft.row - scroll) as u16,
+    );
+}
+
+fn draw_switcher<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let area = centered_rect(60, 60, area);
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title("Switch task · Enter bind, Esc cancel")
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::Yellow))
+        .borders(Borders::all());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let prompt = format!("> {}", app.switcher.query);
+    f.set_cursor(chunks[0].x + prompt.width() as u16, chunks[0].y);
+    f.render_widget(Paragraph::new(prompt), chunks[0]);
+
+    let bound = app.tomato.where_idx();
+    let items: Vec<_> = app
+        .switcher
+        .items()
+        .iter()
+        .map(|item| {
+            let (iidx, tidx) = item.location;
+            let inv = &app.inventory.inventory_list[iidx];
+            let task = &app.inventory.tasks_list[iidx][tidx];
+            let mut line = vec![
+                Span::styled("● ", Style::default().fg(inv.color.into())),
+                Span::styled(
+                    format!("{} / ", inv.name),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ];
+            line.extend(highlight_chars(
+                &task.name,
+                &item.positions,
+                Style::default(),
+            ));
+            if bound == Some(item.location) {
+                line.push(Span::styled(" · bound", Style::default().fg(Color::Green)));
+            } else if item.recent {
+                line.push(Span::styled(
+                    " · recent",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            ListItem::new(Spans::from(line))
+        })
+        .collect();
+
+    let mut state = ListState::default();
+    state.select(if items.is_empty() {
+        None
+    } else {
+        Some(app.switcher.selected)
+    });
+    let list = List::new(items)
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
 fn draw_popup<B: Backend>(f: &mut Frame<B>, msg: &str, area: Rect) {
     let area = centered_rect(60, 20, area);
     f.render_widget(Clear, area);
@@ -115,6 +267,75 @@ fn draw_popup<B: Backend>(f: &mut Frame<B>, msg: &str, area: Rect) {
     f.render_widget(paragraph, area);
 }
 
+fn draw_confirm<B: Backend>(f: &mut Frame<B>, confirm: &Confirmation, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    f.render_widget(Clear, area);
+    let text = vec![
+        Spans::from(Span::styled(
+            &confirm.title,
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(confirm.detail.as_str()),
+        Spans::from(""),
+        Spans::from("[y]es / [n]o"),
+    ];
+    let paragraph = Paragraph::new(text).alignment(Alignment::Center).block(
+        Block::default()
+            .title("Confirm")
+            .border_type(BorderType::Double)
+            .border_style(Style::default().fg(Color::Red))
+            .borders(Borders::all()),
+    );
+    f.render_widget(paragraph, area);
+}
+
+fn draw_archive<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let area = centered_rect(60, 60, area);
+    f.render_widget(Clear, area);
+
+    let mut state = ListState::default();
+    state.select(app.archive.selected);
+    let date = |ts: Option<i64>| {
+        ts.map(|ts| {
+            chrono::Local
+                .timestamp(ts, 0)
+                .format("%Y-%-m-%-d")
+                .to_string()
+        })
+        .unwrap_or_default()
+    };
+    let items: Vec<_> = app
+        .archive
+        .items
+        .iter()
+        .map(|item| {
+            let spans = match item {
+                ArchivedItem::Inventory(inv) => vec![
+                    Span::styled("●", Style::default().fg(inv.color.into())),
+                    Span::raw(format!(" {}  ", inv.name)),
+                    Span::styled(date(inv.archived_at), Style::default().fg(Color::DarkGray)),
+                ],
+                ArchivedItem::Task(task, inv_name) => vec![
+                    Span::raw(format!("  {} · {}  ", inv_name, task.name)),
+                    Span::styled(date(task.archived_at), Style::default().fg(Color::DarkGray)),
+                ],
+            };
+            ListItem::new(Spans::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title("Archived · [r]estore [^d]purge")
+                .border_type(BorderType::Thick)
+                .borders(Borders::all()),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+    f.render_stateful_widget(list, area, &mut state);
+}
+
 fn draw_navitabs<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     let titles = app
         .tabs
@@ -143,8 +364,67 @@ fn draw_inventory_tab<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
         .constraints([Constraint::Length(20), Constraint::Min(0)].as_ref())
         .split(area);
 
+    let right = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(10)].as_ref())
+        .split(chunks[1]);
+
     draw_inventory_list(f, app, chunks[0]);
-    draw_task_list(f, app, chunks[1]);
+    draw_task_list(f, app, right[0]);
+    draw_task_details(f, app, right[1]);
+}
+
+fn draw_task_details<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    if app.inventory.inventory_selected.is_none() {
+        return;
+    }
+    let block = Block::default().borders(Borders::ALL).title("Details");
+    let task = match app.inventory.get_task_location() {
+        Some((iidx, tidx)) => &app.inventory.tasks_list[iidx][tidx],
+        None => {
+            f.render_widget(block, area);
+            return;
+        }
+    };
+
+    let day = |ts: i64| {
+        chrono::Local
+            .timestamp(ts, 0)
+            .format("%Y-%-m-%-d")
+            .to_string()
+    };
+    let mut dates = format!("created {}", day(task.create_at));
+    if let Some(completed_at) = task.completed_at {
+        dates.push_str(&format!(" · done {}", day(completed_at)));
+    }
+    let tomatos = match task.estimate {
+        Some(estimate) => format!("{}/{} 🍅", task.spent_tomatos, estimate),
+        None => format!("{} 🍅", task.spent_tomatos),
+    };
+
+    let mut text = vec![
+        Spans::from(Span::styled(
+            task.name.as_str(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(format!(
+            "{} · {} · {}",
+            dates,
+            tomatos,
+            format_duration(task.spent_seconds)
+        )),
+        Spans::from(""),
+    ];
+    match &task.notes {
+        Some(notes) => text.extend(notes.lines().map(Spans::from)),
+        None => text.push(Spans::from(Span::styled(
+            "no notes, n to write some",
+            Style::default().fg(Color::DarkGray),
+        ))),
+    }
+
+    let paragraph = Paragraph::new(text).wrap(Wrap { trim: false }).block(block);
+    f.render_widget(paragraph, area);
 }
 
 fn draw_inventory_list<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
@@ -154,13 +434,28 @@ fn draw_inventory_list<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
         .inventory
         .inventory_list
         .iter()
-        .map(|i| {
-            let item = Spans::from(vec![
+        .enumerate()
+        .map(|(iidx, i)| {
+            let mut item = vec![
                 Span::styled("●", Style::default().fg(i.color.into())),
                 Span::raw(" "),
                 Span::raw(&i.name),
-            ]);
-            ListItem::new(item)
+            ];
+            // matches of a search in every inventory
+            if app.inventory.searches_all() {
+                let matches = app.inventory.visible_tasks(iidx).len();
+                let style = match matches {
+                    0 => Style::default().fg(Color::DarkGray),
+                    _ => Style::default().fg(Color::Yellow),
+                };
+                item.push(Span::styled(format!(" ({})", matches), style));
+            }
+            // the daily goal if any, the tomato tab has both
+            if let Some(state) = app.goals.states(Some(i.id)).first() {
+                item.push(Span::raw(" "));
+                item.push(goal_span(state));
+            }
+            ListItem::new(Spans::from(item))
         })
         .collect();
 
@@ -178,13 +473,40 @@ fn draw_inventory_list<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     f.render_stateful_widget(list, area, &mut state);
 }
 
+// the chars at positions stand out from the rest of the text
+fn highlight_chars<'a>(text: &'a str, positions: &[usize], style: Style) -> Vec<Span<'a>> {
+    let matched = style.fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut start_matched = false;
+    for (idx, (offset, _)) in text.char_indices().enumerate() {
+        let is_matched = positions.contains(&idx);
+        if is_matched != start_matched && offset > start {
+            let part_style = if start_matched { matched } else { style };
+            spans.push(Span::styled(&text[start..offset], part_style));
+            start = offset;
+        }
+        start_matched = is_matched;
+    }
+    if start < text.len() {
+        let part_style = if start_matched { matched } else { style };
+        spans.push(Span::styled(&text[start..], part_style));
+    }
+    spans
+}
+
 fn draw_task_list<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     if app.inventory.inventory_selected.is_none() {
         return;
     }
     let task_idx = app.inventory.inventory_selected.unwrap();
+    // the list shows the filtered view, the selection is an index in all tasks
+    let visible = app.inventory.visible_tasks(task_idx);
     let mut state = ListState::default();
-    state.select(app.inventory.task_selected[task_idx]);
+    state.select(
+        app.inventory.task_selected[task_idx]
+            .and_then(|selected| visible.iter().position(|tidx| *tidx == selected)),
+    );
 
     // right alignment manually & center sep line
     let width = (area.width - 2) as usize; //  2 boderlines
@@ -196,24 +518,58 @@ fn draw_task_list<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
         sep_line.push('-');
     }
 
-    let task_last_idx = app.inventory.tasks_list[task_idx].len();
+    let task_last_idx = visible.len();
 
-    let items: Vec<_> = app.inventory.tasks_list[task_idx]
+    let items: Vec<_> = visible
         .iter()
+        .map(|tidx| &app.inventory.tasks_list[task_idx][*tidx])
         .enumerate()
         .map(|(idx, t)| {
-            let mut line = t.name.clone();
             let date = chrono::Local
                 .timestamp(t.create_at, 0)
                 .format("%Y-%-m-%-d")
                 .to_string();
-            let padding = " ".repeat(width.saturating_sub(date.width() + line.width()));
-            line.push_str(&padding);
+            // padded apart from the name, which may be crossed out
+            let mut line = " ".repeat(width.saturating_sub(date.width() + t.name.width()));
             line.push_str(&date);
 
+            // overruns in red
+            let (tomatos, style) = match t.tomatos_left() {
+                Some(left) if left < 0 => (
+                    format!(
+                        "{}/{} 🍅 {} over",
+                        t.spent_tomatos,
+                        t.estimate.unwrap(),
+                        -left
+                    ),
+                    Style::default().fg(Color::Red),
+                ),
+                Some(left) => (
+                    format!(
+                        "{}/{} 🍅 {} left",
+                        t.spent_tomatos,
+                        t.estimate.unwrap(),
+                        left
+                    ),
+                    Style::default(),
+                ),
+                None => (format!("{} 🍅", t.spent_tomatos), Style::default()),
+            };
+            let name_style = match t.completed_at {
+                Some(_) => Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::CROSSED_OUT),
+                None => Style::default(),
+            };
+            let positions = app.inventory.matched_chars(&t.name).unwrap_or_default();
+            let mut name = highlight_chars(&t.name, &positions, name_style);
+            name.push(Span::raw(line));
             let mut list_item = vec![
-                Spans::from(line),
-                Spans::from(format!("🍅 {} minutes", t.spent_minutes)),
+                Spans::from(name),
+                Spans::from(vec![
+                    Span::styled(tomatos, style),
+                    Span::raw(format!("  {}", format_duration(t.spent_seconds))),
+                ]),
             ];
 
             if idx < task_last_idx {
@@ -226,13 +582,40 @@ fn draw_task_list<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
         })
         .collect();
 
-    let border_type = thick_border_or_not(app, ActiveBlock::TaskList);
+    // the search is typed in the task list
+    let searching = app.active_block() == ActiveBlock::Search;
+    let border_type = if searching {
+        BorderType::Thick
+    } else {
+        thick_border_or_not(app, ActiveBlock::TaskList)
+    };
+
+    // estimated work not done yet
+    let left: i32 = app.inventory.tasks_list[task_idx]
+        .iter()
+        .filter(|t| t.completed_at.is_none())
+        .filter_map(|t| t.tomatos_left())
+        .filter(|left| *left > 0)
+        .sum();
+    let mut title = format!("Task · {}", app.inventory.task_filter.title());
+    if left > 0 {
+        title.push_str(&format!(" · {} 🍅 left", left));
+    }
+    if let Some(search) = &app.inventory.search {
+        title.push_str(&format!(" · /{}", search.query));
+        if searching {
+            f.set_cursor(area.x + 1 + title.width() as u16, area.y);
+        }
+        if search.all_inventories {
+            title.push_str(" in all");
+        }
+    }
 
     let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Task")
+                .title(title)
                 .border_type(border_type),
         )
         .highlight_style(Style::default().add_modifier(Modifier::BOLD));
@@ -240,15 +623,279 @@ fn draw_task_list<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     f.render_stateful_widget(list, area, &mut state);
 }
 
-fn draw_statistic_tab<B: Backend>(f: &mut Frame<B>, _app: &App, area: Rect) {
+fn draw_statistic_tab<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(2),
+                Constraint::Min(6),
+                Constraint::Length(10),
+                Constraint::Length(8),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    let header = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(24), Constraint::Min(0)].as_ref())
+        .split(chunks[0]);
+
+    let titles = StatRange::ALL
+        .iter()
+        .map(|r| Spans::from(Span::styled(r.title(), Style::default().fg(Color::Green))))
+        .collect();
+    let selected = StatRange::ALL
+        .iter()
+        .position(|r| *r == app.statistics.range)
+        .unwrap_or(0);
+    let tabs = Tabs::new(titles)
+        .block(
+            Block::default()
+                .borders(Borders::BOTTOM)
+                .border_style(Style::default().fg(Color::DarkGray)),
+        )
+        .highlight_style(Style::default().fg(Color::Yellow))
+        .select(selected);
+    f.render_widget(tabs, header[0]);
+
+    let stat = match &app.statistics.data {
+        Some(stat) => stat,
+        None => {
+            f.render_widget(Paragraph::new("Loading..."), chunks[1]);
+            return;
+        }
+    };
+
+    let completion = match stat.completion_rate() {
+        Some(rate) => format!("{}% completed", rate),
+        None => "no session".to_owned(),
+    };
+    let summary = format!(
+        "🍅 {}  ·  {}  ·  {} focused",
+        stat.total_count,
+        completion,
+        format_duration(stat.total_seconds)
+    );
     f.render_widget(
-        Paragraph::new("there are a lot of graphes"),
-        Rect::new(area.x, area.bottom() - 1, area.width, 1),
+        Paragraph::new(summary).alignment(Alignment::Right),
+        header[1],
+    );
+
+    draw_focus_chart(f, stat, chunks[1]);
+
+    // weekday labels, 2 columns per week, 2 borderlines, but leave some for the day list,
+    // the heatmap narrows to a column per week
+    let heatmap_width = (4 + 2 * 53 + 2).min(area.width * 2 / 3);
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(heatmap_width), Constraint::Min(0)].as_ref())
+        .split(chunks[2]);
+    draw_heatmap(f, app, stat, middle[0]);
+    draw_day_tomatos(f, app, middle[1]);
+
+    let bottom = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)].as_ref())
+        .split(chunks[3]);
+    draw_inventory_breakdown(f, stat, bottom[0]);
+    let side = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(4), Constraint::Min(0)].as_ref())
+        .split(bottom[1]);
+    draw_streaks(f, stat, side[0]);
+    draw_estimates(f, stat, side[1]);
+}
+
+fn draw_heatmap<B: Backend>(f: &mut Frame<B>, app: &App, stat: &Statistics, area: Rect) {
+    let selected = if app.active_block() == ActiveBlock::Heatmap {
+        app.statistics.day_selected
+    } else {
+        None
+    };
+    let palette: Vec<Color> = HEATMAP_PALETTE.iter().map(|&c| c.into()).collect();
+    let heatmap = Heatmap::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Last year")
+                .border_type(thick_border_or_not(app, ActiveBlock::Heatmap)),
+        )
+        .counts(&stat.day_counts)
+        .end(chrono::Local::today().naive_local())
+        .selected(selected)
+        .palette(&palette);
+    f.render_widget(heatmap, area);
+}
+
+fn draw_day_tomatos<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let day = match app.statistics.day_selected {
+        Some(day) if app.active_block() == ActiveBlock::Heatmap => day,
+        _ => return,
+    };
+
+    let items: Vec<_> = app
+        .statistics
+        .day_tomatos
+        .iter()
+        .map(|t| {
+            let clock = |ts| chrono::Local.timestamp(ts, 0).format("%H:%M").to_string();
+            let mut spans = vec![
+                Span::raw(format!("{}-{} ", clock(t.start_time), clock(t.end_time))),
+                Span::styled("●", Style::default().fg(t.color.into())),
+                Span::raw(format!(" {} · {}", t.inventory, t.task)),
+            ];
+            match t.outcome {
+                Outcome::Completed => {}
+                Outcome::Interrupted => spans.push(Span::styled(
+                    " interrupted",
+                    Style::default().fg(Color::DarkGray),
+                )),
+                Outcome::Skipped => spans.push(Span::styled(
+                    " skipped",
+                    Style::default().fg(Color::DarkGray),
+                )),
+            }
+            ListItem::new(Spans::from(spans))
+        })
+        .collect();
+
+    let title = format!(
+        "{} · {} 🍅",
+        day.format("%Y-%-m-%-d"),
+        app.statistics
+            .day_tomatos
+            .iter()
+            .filter(|t| t.outcome == Outcome::Completed)
+            .count()
     );
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(list, area);
+}
+
+fn draw_focus_chart<B: Backend>(f: &mut Frame<B>, stat: &Statistics, area: Rect) {
+    let data: Vec<(&str, u64)> = stat.bars.iter().map(|(l, v)| (l.as_str(), *v)).collect();
+    let inner_width = area.width.saturating_sub(2);
+    let bar_width = (inner_width / data.len().max(1) as u16)
+        .saturating_sub(1)
+        .max(1);
+
+    let chart = BarChart::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Focus minutes"),
+        )
+        .data(&data)
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(Color::Red))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Red))
+        .label_style(Style::default().fg(Color::Gray));
+    f.render_widget(chart, area);
+}
+
+fn draw_inventory_breakdown<B: Backend>(f: &mut Frame<B>, stat: &Statistics, area: Rect) {
+    let name_width = 12;
+    let value_width = 14;
+    let width = area.width.saturating_sub(2) as usize; // 2 borderlines
+    let bar_width = width.saturating_sub(name_width + value_width + 2);
+    let max = stat
+        .inventories
+        .first()
+        .map(|i| i.seconds)
+        .unwrap_or(0)
+        .max(1);
+
+    let items: Vec<_> = stat
+        .inventories
+        .iter()
+        .map(|i| {
+            let padding = " ".repeat(name_width.saturating_sub(i.name.width()));
+            let len = (i.seconds.max(0) as usize * bar_width) / max as usize;
+            ListItem::new(Spans::from(vec![
+                Span::raw(&i.name),
+                Span::raw(padding),
+                Span::raw(" "),
+                Span::styled("█".repeat(len), Style::default().fg(i.color.into())),
+                Span::raw(" "),
+                Span::raw(format!("{} ({})", format_duration(i.seconds), i.count)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title("Inventory"));
+    f.render_widget(list, area);
+}
+
+fn draw_streaks<B: Backend>(f: &mut Frame<B>, stat: &Statistics, area: Rect) {
+    let text = vec![
+        Spans::from(format!("current: {} days", stat.current_streak)),
+        Spans::from(format!("longest: {} days", stat.longest_streak)),
+    ];
+    let paragraph =
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Streak"));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_estimates<B: Backend>(f: &mut Frame<B>, stat: &Statistics, area: Rect) {
+    let text = match (stat.within_estimate_rate(), stat.estimate_ratio()) {
+        (Some(rate), Some(ratio)) => vec![
+            Spans::from(format!(
+                "{}% of {} tasks within estimate",
+                rate, stat.estimates.tasks
+            )),
+            Spans::from(format!("{:.1}x the estimated tomatoes", ratio)),
+        ],
+        _ => vec![Spans::from("no estimated task")],
+    };
+    let paragraph =
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Estimate"));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_settings_tab<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let mut state = ListState::default();
+    if app.active_block() == ActiveBlock::Settings {
+        state.select(Some(app.settings.selected));
+    }
+
+    let width = area.width.saturating_sub(2) as usize; // 2 borderlines
+    let config = app.tomato.config();
+    let items: Vec<_> = SettingField::ALL
+        .iter()
+        .map(|field| {
+            let value = match field.value(config) {
+                _ if !field.is_toggle() => format!("{} {}", field.value(config), field.unit()),
+                0 => "off".to_owned(),
+                _ => "on".to_owned(),
+            };
+            let padding = " ".repeat(width.saturating_sub(field.title().width() + value.width()));
+            ListItem::new(Spans::from(vec![
+                Span::raw(field.title()),
+                Span::raw(padding),
+                Span::styled(value, Style::default().fg(Color::Yellow)),
+            ]))
+        })
+        .collect();
+
+    let border_type = thick_border_or_not(app, ActiveBlock::Settings);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Timer")
+                .border_type(border_type),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+
+    f.render_stateful_widget(list, area, &mut state);
 }
 
 fn draw_tomato_tab<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     let countdown_area = draw_countdown(f, app, area);
+    draw_goals(f, app, area, countdown_area);
 
     // debug info
     f.render_widget(
@@ -263,6 +910,41 @@ fn draw_tomato_tab<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
     );
 }
 
+// under the countdown, goals of all inventories and of the bound one
+fn draw_goals<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect, countdown_area: Rect) {
+    let mut owners = vec![("All".to_owned(), None)];
+    if let Some((iidx, _)) = app.tomato.where_idx() {
+        let inv = &app.inventory.inventory_list[iidx];
+        owners.push((inv.name.clone(), Some(inv.id)));
+    }
+
+    let mut lines = Vec::new();
+    for (name, id) in owners {
+        let states = app.goals.states(id);
+        if states.is_empty() {
+            continue;
+        }
+        let mut line = vec![Span::raw(name + ":")];
+        for state in states.iter() {
+            line.push(Span::raw(format!(" {} ", state.period.span())));
+            line.push(goal_span(state));
+            line.push(Span::raw(" 🍅"));
+        }
+        lines.push(Spans::from(line));
+    }
+
+    // the last line is taken by the status
+    let y = countdown_area.bottom() + 1;
+    let height = (area.bottom().saturating_sub(1)).saturating_sub(y);
+    if lines.is_empty() || height == 0 {
+        return;
+    }
+    f.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center),
+        Rect::new(area.x, y, area.width, height.min(2)),
+    );
+}
+
 fn draw_countdown<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) -> Rect {
     // r: resize  w: widget
     let (wh, ww) = COUNTDOWN_SIZES
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Widget},
+};
+
+const CELL: &str = "■";
+const WEEKDAYS: &[&str] = &["Mon", "", "Wed", "", "Fri", "", "Sun"];
+// weekday labels take 4 columns
+const LABEL_WIDTH: u16 = 4;
+// the weeks touched by a year of days
+const YEAR_WEEKS: i64 = 53;
+
+fn monday_of(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+// palette index of a count, 0 for none and the last one for `max`
+fn level(count: i64, max: i64, levels: usize) -> usize {
+    let levels = levels as i64;
+    if count <= 0 || max <= 0 || levels < 2 {
+        return 0;
+    }
+    // ceil, so that any count is visible
+    let level = (count * (levels - 1) + max - 1) / max;
+    level.min(levels - 1) as usize
+}
+
+/// A calendar heatmap, one column per week, one row per weekday.
+/// The last column is the week of `end`, unless the selected day is further back.
+#[derive(Debug, Clone)]
+pub struct Heatmap<'a> {
+    block: Option<Block<'a>>,
+    counts: Option<&'a HashMap<NaiveDate, i64>>,
+    end: NaiveDate,
+    selected: Option<NaiveDate>,
+    palette: &'a [Color],
+}
+
+impl<'a> Default for Heatmap<'a> {
+    fn default() -> Heatmap<'a> {
+        Heatmap {
+            block: None,
+            counts: None,
+            end: NaiveDate::from_ymd(1970, 1, 1),
+            selected: None,
+            palette: &[Color::DarkGray, Color::Green],
+        }
+    }
+}
+
+impl<'a> Heatmap<'a> {
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn counts(mut self, counts: &'a HashMap<NaiveDate, i64>) -> Self {
+        self.counts = Some(counts);
+        self
+    }
+
+    pub fn end(mut self, end: NaiveDate) -> Self {
+        self.end = end;
+        self
+    }
+
+    pub fn selected(mut self, selected: Option<NaiveDate>) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// colors from zero to the most count, at least 2 of them
+    pub fn palette(mut self, palette: &'a [Color]) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    fn level_color(&self, count: i64, max: i64) -> Color {
+        self.palette[level(count, max, self.palette.len())]
+    }
+}
+
+impl<'a> Widget for Heatmap<'a> {
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
+        let area = match self.block.take() {
+            Some(b) => {
+                let inner_area = b.inner(area);
+                b.render(area, buf);
+                inner_area
+            }
+            None => area,
+        };
+        // a row for month labels and 7 for weekdays
+        if area.height < 8 || area.width <= LABEL_WIDTH {
+            return;
+        }
+
+        let empty = HashMap::new();
+        let counts = self.counts.unwrap_or(&empty);
+        let max = counts.values().copied().max().unwrap_or(0);
+
+        // 2 columns per week when the year fits, else 1
+        let columns = (area.width - LABEL_WIDTH) as i64;
+        let step = if columns >= 2 * YEAR_WEEKS { 2 } else { 1 };
+        let weeks = (columns / step).min(YEAR_WEEKS);
+        let mut first_monday = monday_of(self.end) - Duration::weeks(weeks - 1);
+        // scroll back to a selected day out of sight
+        if let Some(selected) = self.selected {
+            first_monday = first_monday.min(monday_of(selected));
+        }
+
+        for (row, label) in WEEKDAYS.iter().enumerate() {
+            buf.set_string(
+                area.x,
+                area.y + 1 + row as u16,
+                label,
+                Style::default().fg(Color::Gray),
+            );
+        }
+
+        let mut last_month = None;
+        let mut label_end = area.x;
+        for week in 0..weeks {
+            let monday = first_monday + Duration::weeks(week);
+            let x = area.x + LABEL_WIDTH + (week * step) as u16;
+
+            if last_month != Some(monday.month()) && x >= label_end && x + 3 <= area.right() {
+                buf.set_string(
+                    x,
+                    area.y,
+                    monday.format("%b").to_string(),
+                    Style::default().fg(Color::Gray),
+                );
+                last_month = Some(monday.month());
+                label_end = x + 4;
+            }
+
+            for row in 0..7 {
+                let day = monday + Duration::days(row);
+                if day > self.end {
+                    break;
+                }
+                let count = *counts.get(&day).unwrap_or(&0);
+                let mut style = Style::default().fg(self.level_color(count, max));
+                if self.selected == Some(day) {
+                    style = style.bg(Color::White).add_modifier(Modifier::BOLD);
+                }
+                buf.set_string(x, area.y + 1 + row as u16, CELL, style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_buckets() {
+        // nothing, or no palette to pick from
+        assert_eq!(level(0, 8, 5), 0);
+        assert_eq!(level(3, 0, 5), 0);
+        assert_eq!(level(3, 8, 1), 0);
+        // any count shows, the most is the top level
+        assert_eq!(level(1, 100, 5), 1);
+        assert_eq!(level(25, 100, 5), 1);
+        assert_eq!(level(26, 100, 5), 2);
+        assert_eq!(level(75, 100, 5), 3);
+        assert_eq!(level(76, 100, 5), 4);
+        assert_eq!(level(100, 100, 5), 4);
+        assert_eq!(level(120, 100, 5), 4);
+    }
+
+    #[test]
+    fn monday_of_week() {
+        let monday = NaiveDate::from_ymd(2026, 10, 12);
+        assert_eq!(monday_of(monday), monday);
+        assert_eq!(monday_of(NaiveDate::from_ymd(2026, 10, 18)), monday);
+    }
+}
@@ -1,5 +1,7 @@
 mod countdown;
 mod digit;
+mod heatmap;
 
 pub use countdown::Countdown;
 pub use digit::Digit;
+pub use heatmap::Heatmap;